@@ -1,4 +1,21 @@
 var MetaData
+
+// reading time buckets of the length filter in minutes, see MetaData.ReadingTime
+var lengthBuckets = {
+    "short": [0, 5],
+    "medium": [5, 15],
+    "long": [15, Infinity]
+}
+
+var articlesByLength = function(length) {
+    var bucket = lengthBuckets[length]
+    return Object.keys(MetaData.ReadingTime)
+        .filter(function(id) {
+            var minutes = MetaData.ReadingTime[id]
+            return minutes > bucket[0] && minutes <= bucket[1]
+        })
+        .map(function(id) { return parseInt(id) })
+}
 function getURLParameter(name) {
     return decodeURIComponent((new RegExp('[?|&]' + name + '=' + '([^&;]+?)(&|#|;|$)').exec(location.search)||[,""])[1].replace(/\+/g, '%20'))||null
 }
@@ -49,6 +66,8 @@ var setFilter = function(filter, addHistory) {
         selection = MetaData.Tags[identifier]
     } else if (type == "series" && typeof(MetaData.Series[identifier]) !== "undefined") {
         selection = MetaData.Series[identifier]
+    } else if (type == "length" && typeof(lengthBuckets[identifier]) !== "undefined") {
+        selection = articlesByLength(identifier)
     } else {
         console.log("removing filter selection")
         var timelineEvents = document.querySelectorAll('.posting_div');
@@ -105,6 +124,9 @@ var setFilter = function(filter, addHistory) {
     if (type == "series" && typeof(MetaData.Series[identifier]) !== "undefined") {
         filterSelection.innerHTML = 'Your selection: <a class="seriesbtn btn btn-primary"">' + identifier + '</a>'
     }
+    if (type == "length" && typeof(lengthBuckets[identifier]) !== "undefined") {
+        filterSelection.innerHTML = 'Your selection: <a class="lengthbtn btn btn-primary"">' + identifier + ' reads</a>'
+    }
     toPostSelectionView();
 }
 
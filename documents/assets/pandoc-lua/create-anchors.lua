@@ -1,6 +1,6 @@
 function Pandoc(doc)
 -- Create and number sections
-doc.blocks = pandoc.utils.make_sections(true, nil, doc.blocks)
+doc.blocks = pandoc.utils.make_sections(doc.meta["pankat-numbered"] ~= false, nil, doc.blocks)
 
 -- Table to track heading IDs for unique anchors
 local heading_id_counts = {}
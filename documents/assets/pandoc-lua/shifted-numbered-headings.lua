@@ -1,8 +1,9 @@
 -- https://stackoverflow.com/questions/73417580/specify-pandoc-html-numbering-to-start-from-h2 thanks!
 function Pandoc (doc)
-  -- Create and number sections. Setting the first parameter to
-  -- `true` ensures that headings are numbered.
-  doc.blocks = pandoc.utils.make_sections(true, nil, doc.blocks)
+  -- Create and number sections. The first parameter turns the
+  -- numbering on, [[!options numbered=false]] sets pankat-numbered
+  -- to false.
+  doc.blocks = pandoc.utils.make_sections(doc.meta["pankat-numbered"] ~= false, nil, doc.blocks)
 
   -- Shift the heading levels by 1
   doc.blocks = doc.blocks:walk {
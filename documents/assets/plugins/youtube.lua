@@ -0,0 +1,15 @@
+-- [[!youtube dQw4w9WgXcQ]] embeds a youtube video
+--
+-- pankat calls render(args, article, params) for every [[!youtube ...]] directive,
+-- args is the argument string and article a table with title, tags, series,
+-- summary and modification_date which can be changed by the script. params
+-- holds the parsed arguments, positional ones at params[1], params[2], ...
+-- and key=value ones as params.key.
+-- the returned markdown replaces the directive.
+function render(args, article, params)
+  local id = params and params[1]
+  if id == nil then
+    error("youtube: missing video id")
+  end
+  return '<iframe width="560" height="315" src="https://www.youtube-nocookie.com/embed/' .. id .. '" frameborder="0" allowfullscreen></iframe>'
+end
@@ -39,6 +39,8 @@
               just
               sqlite
               pandoc
+              graphviz  # optional, [[!graphviz]] diagrams
+              plantuml  # optional, [[!plantuml]] diagrams
               
               cmake
               clang
@@ -0,0 +1 @@
+drop table article_links;
\ No newline at end of file
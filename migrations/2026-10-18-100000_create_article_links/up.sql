@@ -0,0 +1,6 @@
+CREATE TABLE article_links (
+  src_file_name TEXT NOT NULL,
+  kind TEXT NOT NULL,
+  target TEXT NOT NULL,
+  PRIMARY KEY (src_file_name, kind, target)
+);
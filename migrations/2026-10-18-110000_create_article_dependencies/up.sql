@@ -0,0 +1,5 @@
+CREATE TABLE article_dependencies (
+  src_file_name TEXT NOT NULL,
+  path TEXT NOT NULL,
+  PRIMARY KEY (src_file_name, path)
+);
\ No newline at end of file
@@ -0,0 +1 @@
+alter table articles drop column numbered;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE articles ADD COLUMN numbered BOOLEAN;
\ No newline at end of file
@@ -0,0 +1,6 @@
+alter table articles drop column keywords;
+alter table articles drop column description;
+alter table articles drop column canonical;
+alter table articles drop column lang;
+alter table articles drop column updated;
+alter table articles drop column author;
@@ -0,0 +1,6 @@
+ALTER TABLE articles ADD COLUMN author TEXT;
+ALTER TABLE articles ADD COLUMN updated TIMESTAMP;
+ALTER TABLE articles ADD COLUMN lang TEXT;
+ALTER TABLE articles ADD COLUMN canonical TEXT;
+ALTER TABLE articles ADD COLUMN description TEXT;
+ALTER TABLE articles ADD COLUMN keywords TEXT;
@@ -0,0 +1,2 @@
+alter table articles drop column expire_at;
+alter table articles drop column publish_at;
@@ -0,0 +1,2 @@
+ALTER TABLE articles ADD COLUMN publish_at TIMESTAMP;
+ALTER TABLE articles ADD COLUMN expire_at TIMESTAMP;
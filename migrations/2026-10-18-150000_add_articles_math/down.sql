@@ -0,0 +1 @@
+alter table articles drop column math;
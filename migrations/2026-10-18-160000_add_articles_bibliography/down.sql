@@ -0,0 +1,2 @@
+alter table articles drop column csl;
+alter table articles drop column bibliography;
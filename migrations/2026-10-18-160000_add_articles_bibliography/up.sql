@@ -0,0 +1,2 @@
+ALTER TABLE articles ADD COLUMN bibliography TEXT;
+ALTER TABLE articles ADD COLUMN csl TEXT;
@@ -0,0 +1 @@
+drop table related_articles;
\ No newline at end of file
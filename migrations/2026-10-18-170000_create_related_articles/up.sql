@@ -0,0 +1,6 @@
+CREATE TABLE related_articles (
+  src_file_name TEXT NOT NULL,
+  related_src_file_name TEXT NOT NULL,
+  score DOUBLE NOT NULL,
+  PRIMARY KEY (src_file_name, related_src_file_name)
+);
@@ -0,0 +1,3 @@
+alter table articles drop column reading_time;
+alter table articles drop column code_word_count;
+alter table articles drop column word_count;
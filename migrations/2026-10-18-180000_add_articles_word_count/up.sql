@@ -0,0 +1,3 @@
+ALTER TABLE articles ADD COLUMN word_count INTEGER;
+ALTER TABLE articles ADD COLUMN code_word_count INTEGER;
+ALTER TABLE articles ADD COLUMN reading_time INTEGER;
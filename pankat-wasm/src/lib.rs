@@ -141,6 +141,13 @@ pub fn main_js(subdir: String) -> Result<(), JsValue> {
                                     }
                                     log::info!("redirect")
                                 }
+                                "reload" => {
+                                    // the page around the article changed, e.g. the
+                                    // standalone template
+                                    let window = web_sys::window().unwrap();
+                                    window.location().reload().expect("Failed to reload");
+                                    log::info!("reload")
+                                }
                                 "update" => {
                                     if let Some(value_str) = value.as_str() {
                                         dom_updater.update(format!(
@@ -0,0 +1,122 @@
+use crate::db::cache::{compute_hash, get_cache, set_cache};
+use crate::renderer::diagram::{render_svg, DiagramTool};
+use diesel::prelude::*;
+use regex::Regex;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::Path;
+
+/// directory in the output where the rendered diagrams are written to
+pub const DIAGRAMS_DIR: &str = "diagrams";
+
+/// the cache table stores the svg of a diagram as `diagram:<hash>`, next to the articles
+pub const DIAGRAM_CACHE_PREFIX: &str = "diagram:";
+
+/// a diagram of the article being parsed, it is rendered once the article is parsed
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PendingDiagram {
+    pub tool: DiagramTool,
+    pub hash: String,
+    pub source: String,
+}
+
+thread_local! {
+    static PENDING: RefCell<Vec<PendingDiagram>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn file_name(hash: &str) -> String {
+    format!("{}.svg", hash)
+}
+
+/// remembers the diagram for `publish` and returns the hash its svg file is named after
+pub fn queue(tool: DiagramTool, source: &str) -> String {
+    let hash = compute_hash(format!("{}\n{}", tool.name(), source));
+    PENDING.with(|pending| {
+        pending.borrow_mut().push(PendingDiagram {
+            tool,
+            hash: hash.clone(),
+            source: source.to_string(),
+        })
+    });
+    hash
+}
+
+/// the diagrams queued since the last call
+pub fn take_pending() -> Vec<PendingDiagram> {
+    PENDING.with(|pending| pending.take())
+}
+
+/// writes the svg files of the diagrams to the output, the tools only run for diagrams which
+/// are not in the cache table yet
+pub fn publish(
+    conn: &mut SqliteConnection,
+    diagrams: &[PendingDiagram],
+    output: &Path,
+) -> Result<(), Box<dyn Error>> {
+    for diagram in diagrams {
+        let cache_name = format!("{}{}", DIAGRAM_CACHE_PREFIX, diagram.hash);
+        let svg = match get_cache(conn, cache_name.clone()) {
+            Some(cache_entry) if cache_entry.hash == diagram.hash => cache_entry.html,
+            _ => {
+                println!("Rendering {} diagram {}", diagram.tool.name(), diagram.hash);
+                let svg = render_svg(diagram.tool, &diagram.source)
+                    .map_err(|e| format!("{}: {}", diagram.tool.name(), e))?;
+                set_cache(conn, cache_name, svg.clone(), diagram.hash.clone())?;
+                svg
+            }
+        };
+        let path = output.join(DIAGRAMS_DIR).join(file_name(&diagram.hash));
+        if std::fs::read_to_string(&path).ok().as_deref() != Some(svg.as_str()) {
+            crate::articles::write_to_disk(&svg, &path);
+        }
+    }
+    Ok(())
+}
+
+/// file names in DIAGRAMS_DIR referenced by the html of an article
+pub fn referenced_diagrams(html: &str) -> HashSet<String> {
+    let re = Regex::new(&format!(r#"/{}/([^"\s/]+\.svg)"#, DIAGRAMS_DIR)).unwrap();
+    re.captures_iter(html)
+        .map(|captures| captures[1].to_string())
+        .collect()
+}
+
+#[test]
+fn test_queue_and_publish() {
+    let output = std::env::temp_dir().join(format!("pankat-test-diagrams-{}", std::process::id()));
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    crate::db::initialize_schema(&mut conn);
+
+    take_pending();
+    let hash = queue(DiagramTool::Graphviz, "digraph { a -> b }");
+    assert_ne!(hash, queue(DiagramTool::Mermaid, "digraph { a -> b }"));
+    let pending = take_pending();
+    assert_eq!(pending.len(), 2);
+    assert!(take_pending().is_empty());
+
+    // a cached diagram is published without running the tool
+    let svg = "<svg><text>a -> b</text></svg>".to_string();
+    set_cache(
+        &mut conn,
+        format!("{}{}", DIAGRAM_CACHE_PREFIX, hash),
+        svg.clone(),
+        hash.clone(),
+    )
+    .unwrap();
+    publish(&mut conn, &pending[0..1], &output).unwrap();
+    let path = output.join(DIAGRAMS_DIR).join(file_name(&hash));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), svg);
+
+    let html = format!(
+        r#"<img src="/blog/{}/{}" alt="">"#,
+        DIAGRAMS_DIR,
+        file_name(&hash)
+    );
+    assert_eq!(
+        referenced_diagrams(&html),
+        HashSet::from([file_name(&hash)])
+    );
+
+    std::fs::remove_dir_all(&output).unwrap();
+}
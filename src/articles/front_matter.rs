@@ -0,0 +1,326 @@
+use crate::articles::ArticleWithTags;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde_json::Value;
+use std::error::Error;
+
+/// metadata from a leading `---` (YAML) or `+++` (TOML) block
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    pub modification_date: Option<NaiveDateTime>,
+    pub summary: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub series: Option<String>,
+    pub draft: Option<bool>,
+    pub special_page: Option<bool>,
+    pub author: Option<String>,
+    pub updated: Option<NaiveDateTime>,
+    pub lang: Option<String>,
+    pub canonical: Option<String>,
+    pub description: Option<String>,
+    pub keywords: Option<String>,
+    pub publish_at: Option<NaiveDateTime>,
+    pub expire_at: Option<NaiveDateTime>,
+}
+
+/// splits the front matter from the document
+///
+/// the front matter is replaced by empty lines so line numbers reported for directives in the
+/// remaining document still match the source file.
+pub fn split_front_matter(
+    article_mdwn_raw_string: &str,
+) -> Result<(Option<FrontMatter>, String), Box<dyn Error>> {
+    let delimiter = if article_mdwn_raw_string.starts_with("---") {
+        "---"
+    } else if article_mdwn_raw_string.starts_with("+++") {
+        "+++"
+    } else {
+        return Ok((None, article_mdwn_raw_string.to_string()));
+    };
+
+    let mut lines = article_mdwn_raw_string.split_inclusive('\n');
+    match lines.next() {
+        Some(first_line) if first_line.trim_end() == delimiter => {}
+        _ => return Ok((None, article_mdwn_raw_string.to_string())),
+    }
+
+    let mut source = String::new();
+    let mut line_count = 1;
+    let mut closed = false;
+    for line in lines.by_ref() {
+        line_count += 1;
+        let trimmed = line.trim_end();
+        if trimmed == delimiter || (delimiter == "---" && trimmed == "...") {
+            closed = true;
+            break;
+        }
+        source.push_str(line);
+    }
+    if !closed {
+        return Err(format!("Front matter starting with '{}' is never closed", delimiter).into());
+    }
+
+    let value: Value = if delimiter == "---" {
+        let yaml: serde_yaml::Value = serde_yaml::from_str(&source)
+            .map_err(|e| format!("Front matter is not valid YAML: {}", e))?;
+        serde_json::to_value(yaml)?
+    } else {
+        let table: toml::Table = toml::from_str(&source)
+            .map_err(|e| format!("Front matter is not valid TOML: {}", e))?;
+        toml_to_json(toml::Value::Table(table))
+    };
+
+    let front_matter = FrontMatter::from_value(&value)?;
+    let body: String = "\n".repeat(line_count) + &lines.collect::<String>();
+    Ok((Some(front_matter), body))
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(a) => Value::Array(a.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(t) => {
+            Value::Object(t.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect())
+        }
+    }
+}
+
+/// parses RFC 3339, `YYYY-MM-DD HH:MM[:SS]` and `YYYY-MM-DD` dates
+pub fn parse_date(date: &str) -> Option<NaiveDateTime> {
+    if let Ok(d) = DateTime::parse_from_rfc3339(date) {
+        return Some(d.naive_local());
+    }
+    let date = date.replace('T', " ");
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(d) = NaiveDateTime::parse_from_str(&date, format) {
+            return Some(d);
+        }
+    }
+    NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+}
+
+fn as_string(key: &str, value: &Value) -> Result<String, Box<dyn Error>> {
+    match value {
+        Value::String(s) => Ok(s.trim().to_string()),
+        Value::Number(n) => Ok(n.to_string()),
+        _ => Err(format!("Front matter field '{}' must be a string", key).into()),
+    }
+}
+
+fn as_date(key: &str, value: &Value) -> Result<NaiveDateTime, Box<dyn Error>> {
+    let date = as_string(key, value)?;
+    parse_date(&date).ok_or_else(|| {
+        format!(
+            "Front matter field '{}' has unsupported format '{}', use YYYY-MM-DD HH:MM",
+            key, date
+        )
+        .into()
+    })
+}
+
+fn as_bool(key: &str, value: &Value) -> Result<bool, Box<dyn Error>> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        _ => Err(format!("Front matter field '{}' must be true or false", key).into()),
+    }
+}
+
+impl FrontMatter {
+    fn from_value(value: &Value) -> Result<FrontMatter, Box<dyn Error>> {
+        let mut front_matter = FrontMatter::default();
+        let map = match value {
+            Value::Object(map) => map,
+            Value::Null => return Ok(front_matter),
+            _ => return Err("Front matter must be a key/value mapping".into()),
+        };
+        for (key, value) in map {
+            if value.is_null() {
+                continue;
+            }
+            match key.to_lowercase().as_str() {
+                "title" => front_matter.title = Some(as_string(key, value)?),
+                "date" | "created" => front_matter.modification_date = Some(as_date(key, value)?),
+                "updated" => front_matter.updated = Some(as_date(key, value)?),
+                "author" => front_matter.author = Some(as_string(key, value)?),
+                "lang" => front_matter.lang = Some(as_string(key, value)?),
+                "canonical" => front_matter.canonical = Some(as_string(key, value)?),
+                "keywords" => {
+                    let keywords: String = match value {
+                        Value::Array(keywords) => keywords
+                            .iter()
+                            .map(|keyword| as_string(key, keyword))
+                            .collect::<Result<Vec<String>, Box<dyn Error>>>()?
+                            .join(", "),
+                        _ => as_string(key, value)?,
+                    };
+                    front_matter.keywords = Some(keywords);
+                }
+                "summary" => front_matter.summary = Some(as_string(key, value)?),
+                "description" => front_matter.description = Some(as_string(key, value)?),
+                "tags" => {
+                    let tags: Vec<String> = match value {
+                        Value::Array(tags) => tags
+                            .iter()
+                            .map(|tag| as_string(key, tag))
+                            .collect::<Result<Vec<String>, Box<dyn Error>>>()?,
+                        _ => as_string(key, value)?
+                            .split_whitespace()
+                            .map(|s| s.to_string())
+                            .collect(),
+                    };
+                    front_matter.tags = Some(tags);
+                }
+                "series" => front_matter.series = Some(as_string(key, value)?),
+                "draft" => front_matter.draft = Some(as_bool(key, value)?),
+                "publish_at" => front_matter.publish_at = Some(as_date(key, value)?),
+                "expire_at" => front_matter.expire_at = Some(as_date(key, value)?),
+                "specialpage" | "special_page" => {
+                    front_matter.special_page = Some(as_bool(key, value)?)
+                }
+                _ => {
+                    println!("Warning: ignoring unknown front matter field '{}'", key);
+                }
+            }
+        }
+        Ok(front_matter)
+    }
+
+    /// merges the front matter into the article, a field set by a directive as well is an error
+    pub fn apply(self, article: &mut ArticleWithTags) -> Result<(), Box<dyn Error>> {
+        fn merge<T>(
+            field: &str,
+            directive: &str,
+            from_front_matter: Option<T>,
+            target: &mut Option<T>,
+        ) -> Result<(), Box<dyn Error>> {
+            if let Some(value) = from_front_matter {
+                if target.is_some() {
+                    return Err(format!(
+                        "'{}' is set in the front matter and by a [[!{}]] directive, remove one of them",
+                        field, directive
+                    )
+                    .into());
+                }
+                *target = Some(value);
+            }
+            Ok(())
+        }
+
+        merge("title", "title", self.title, &mut article.title)?;
+        merge(
+            "date",
+            "meta",
+            self.modification_date,
+            &mut article.modification_date,
+        )?;
+        merge("summary", "summary", self.summary, &mut article.summary)?;
+        merge("tags", "tag", self.tags, &mut article.tags)?;
+        merge("series", "series", self.series, &mut article.series)?;
+        merge("draft", "draft", self.draft, &mut article.draft)?;
+        merge(
+            "specialpage",
+            "specialpage",
+            self.special_page,
+            &mut article.special_page,
+        )?;
+        merge("author", "meta", self.author, &mut article.author)?;
+        merge("updated", "meta", self.updated, &mut article.updated)?;
+        merge("lang", "meta", self.lang, &mut article.lang)?;
+        merge("canonical", "meta", self.canonical, &mut article.canonical)?;
+        merge(
+            "description",
+            "meta",
+            self.description,
+            &mut article.description,
+        )?;
+        merge("keywords", "meta", self.keywords, &mut article.keywords)?;
+        merge(
+            "publish_at",
+            "publish_at",
+            self.publish_at,
+            &mut article.publish_at,
+        )?;
+        merge(
+            "expire_at",
+            "expire_at",
+            self.expire_at,
+            &mut article.expire_at,
+        )?;
+        crate::articles::plugins::schedule::check_schedule(article)
+    }
+}
+
+#[test]
+fn test_split_front_matter_yaml() {
+    let input = "---\ntitle: Nix on Windows\ndate: 2024-04-12 20:53\ntags: [nix, windows]\ndraft: false\n---\nhi!\n";
+    let (front_matter, body) = split_front_matter(input).unwrap();
+    let front_matter = front_matter.unwrap();
+    assert_eq!(front_matter.title, Some("Nix on Windows".to_string()));
+    assert_eq!(
+        front_matter.modification_date,
+        NaiveDateTime::parse_from_str("2024-04-12 20:53", "%Y-%m-%d %H:%M").ok()
+    );
+    assert_eq!(
+        front_matter.tags,
+        Some(vec!["nix".to_string(), "windows".to_string()])
+    );
+    assert_eq!(front_matter.draft, Some(false));
+    assert_eq!(body, "\n\n\n\n\n\nhi!\n");
+}
+
+#[test]
+fn test_split_front_matter_toml() {
+    let input =
+        "+++\ntitle = \"libnix\"\ndate = 2024-04-12T20:53:00\ntags = \"nix libnix\"\n+++\nhi!\n";
+    let (front_matter, body) = split_front_matter(input).unwrap();
+    let front_matter = front_matter.unwrap();
+    assert_eq!(front_matter.title, Some("libnix".to_string()));
+    assert_eq!(
+        front_matter.modification_date,
+        NaiveDateTime::parse_from_str("2024-04-12 20:53", "%Y-%m-%d %H:%M").ok()
+    );
+    assert_eq!(
+        front_matter.tags,
+        Some(vec!["nix".to_string(), "libnix".to_string()])
+    );
+    assert_eq!(body, "\n\n\n\n\nhi!\n");
+}
+
+#[test]
+fn test_split_front_matter_none() {
+    let input = "----\nhi!\n";
+    let (front_matter, body) = split_front_matter(input).unwrap();
+    assert_eq!(front_matter, None);
+    assert_eq!(body, input);
+
+    assert!(split_front_matter("---\ntitle: foo\nhi!\n").is_err());
+}
+
+#[test]
+fn test_front_matter_conflict() {
+    let mut article = ArticleWithTags {
+        src_file_name: "example.mdwn".to_string(),
+        dst_file_name: String::new(),
+        title: Some("From Directive".to_string()),
+        ..Default::default()
+    };
+    let front_matter = FrontMatter {
+        series: Some("libnix".to_string()),
+        ..Default::default()
+    };
+    assert!(front_matter.apply(&mut article).is_ok());
+    assert_eq!(article.series, Some("libnix".to_string()));
+
+    let front_matter = FrontMatter {
+        title: Some("From Front Matter".to_string()),
+        ..Default::default()
+    };
+    let error = front_matter.apply(&mut article).unwrap_err().to_string();
+    assert!(error.contains("'title' is set in the front matter and by a [[!title]] directive"));
+}
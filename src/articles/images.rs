@@ -0,0 +1,290 @@
+use crate::articles::utils;
+use image::codecs::jpeg::JpegEncoder;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use regex::Regex;
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// directory in the output where the generated image variants are written to
+pub const IMAGES_DIR: &str = "images";
+
+/// widths of the downscaled variants, images smaller than a width are not upscaled
+const WIDTHS: [u32; 3] = [480, 960, 1920];
+const SIZES: &str = "(max-width: 960px) 100vw, 960px";
+const JPEG_QUALITY: u8 = 85;
+const WEBP_QUALITY: f32 = 80.0;
+
+/// the published variants of a source image
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ResponsiveImage {
+    pub width: u32,
+    pub height: u32,
+    /// (file name in IMAGES_DIR, width) in the original format, smallest first
+    pub variants: Vec<(String, u32)>,
+    /// (file name in IMAGES_DIR, width) as webp, smallest first
+    pub webp_variants: Vec<(String, u32)>,
+}
+
+impl ResponsiveImage {
+    pub fn largest(&self) -> &str {
+        self.variants
+            .last()
+            .map(|(file_name, _)| file_name.as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn srcset(variants: &[(String, u32)], prefix: &str) -> String {
+        variants
+            .iter()
+            .map(|(file_name, width)| format!("{}/{}/{} {}w", prefix, IMAGES_DIR, file_name, width))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// the html for the image, `prefix` is the subdir the site is served from
+    pub fn to_html(&self, prefix: &str, attributes: &str) -> String {
+        let largest = format!("{}/{}/{}", prefix, IMAGES_DIR, self.largest());
+        // the author's width/height, e.g. width="200px", win over the intrinsic size
+        let size = if attributes.contains("width=") || attributes.contains("height=") {
+            String::new()
+        } else {
+            format!(r#" width="{}" height="{}""#, self.width, self.height)
+        };
+        let attributes = if attributes.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", attributes)
+        };
+        format!(
+            r#"<a href="{largest}"><picture><source type="image/webp" srcset="{}" sizes="{SIZES}"><img src="{largest}" srcset="{}" sizes="{SIZES}"{size} loading="lazy"{attributes}></picture></a>"#,
+            ResponsiveImage::srcset(&self.webp_variants, prefix),
+            ResponsiveImage::srcset(&self.variants, prefix),
+        )
+    }
+}
+
+/// finds the file for an `[[!img]]` url in the input, `input/media` or the post directory
+///
+/// urls pointing elsewhere, e.g. https://, and formats which can't be scaled, e.g. svg or
+/// animated gif, return None and are linked as they are.
+pub fn find_source(input: &Path, src_file_name: &str, url: &str) -> Option<PathBuf> {
+    let extension = Path::new(url)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())?;
+    if !matches!(extension.as_str(), "jpg" | "jpeg" | "png" | "webp") {
+        return None;
+    }
+    utils::find_input_path(input, src_file_name, url).filter(|path| path.is_file())
+}
+
+fn hash_bytes(bytes: &[u8]) -> String {
+    use std::hash::Hasher;
+    use twox_hash::XxHash64;
+
+    let mut hasher = XxHash64::default();
+    hasher.write(bytes);
+    format!("{:x}", hasher.finish())
+}
+
+fn variant_widths(width: u32) -> Vec<u32> {
+    let mut widths: Vec<u32> = WIDTHS.iter().copied().filter(|w| *w < width).collect();
+    widths.push(width);
+    widths
+}
+
+/// generates the downscaled and webp variants of `source` into `output/IMAGES_DIR`
+///
+/// variants are named after the content hash of the source, so existing files are reused and
+/// the image is only decoded if one of them is missing. the published copies are re-encoded and
+/// carry no EXIF/GPS metadata.
+pub fn process(source: &Path, output: &Path) -> Result<ResponsiveImage, Box<dyn Error>> {
+    let bytes = std::fs::read(source)
+        .map_err(|e| format!("Can't read image '{}': {}", source.display(), e))?;
+    let hash = hash_bytes(&bytes);
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let reader = ImageReader::new(std::io::Cursor::new(&bytes)).with_guessed_format()?;
+    let format = reader
+        .format()
+        .ok_or_else(|| format!("Unknown image format of '{}'", source.display()))?;
+    let extension = match format {
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::Png => "png",
+        ImageFormat::WebP => "webp",
+        _ => return Err(format!("Unsupported image format of '{}'", source.display()).into()),
+    };
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let (width, height) = match orientation {
+        Orientation::Rotate90
+        | Orientation::Rotate270
+        | Orientation::Rotate90FlipH
+        | Orientation::Rotate270FlipH => {
+            let (w, h) = decoder.dimensions();
+            (h, w)
+        }
+        _ => decoder.dimensions(),
+    };
+
+    let images_dir = output.join(IMAGES_DIR);
+    std::fs::create_dir_all(&images_dir)?;
+
+    let widths = variant_widths(width);
+    let variants: Vec<(String, u32)> = widths
+        .iter()
+        .map(|w| (format!("{}-{}-{}.{}", stem, hash, w, extension), *w))
+        .collect();
+    let webp_variants: Vec<(String, u32)> = widths
+        .iter()
+        .map(|w| (format!("{}-{}-{}.webp", stem, hash, w), *w))
+        .collect();
+
+    let missing = variants
+        .iter()
+        .chain(webp_variants.iter())
+        .any(|(file_name, _)| !images_dir.join(file_name).exists());
+    if missing {
+        println!(" ... generating image variants of {}", source.display());
+        let mut image = DynamicImage::from_decoder(decoder)?;
+        image.apply_orientation(orientation);
+        for ((file_name, w), (webp_file_name, _)) in variants.iter().zip(webp_variants.iter()) {
+            let scaled = if *w == width {
+                image.clone()
+            } else {
+                image.resize(*w, u32::MAX, image::imageops::FilterType::Lanczos3)
+            };
+            write_variant(&scaled, format, &images_dir.join(file_name))?;
+            write_webp(&scaled, &images_dir.join(webp_file_name))?;
+        }
+    }
+
+    Ok(ResponsiveImage {
+        width,
+        height,
+        variants,
+        webp_variants,
+    })
+}
+
+fn write_variant(
+    image: &DynamicImage,
+    format: ImageFormat,
+    path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        ImageFormat::Jpeg => {
+            let file = std::fs::File::create(path)?;
+            let mut writer = std::io::BufWriter::new(file);
+            let encoder = JpegEncoder::new_with_quality(&mut writer, JPEG_QUALITY);
+            image.to_rgb8().write_with_encoder(encoder)?;
+        }
+        ImageFormat::WebP => write_webp(image, path)?,
+        _ => image.save_with_format(path, format)?,
+    }
+    Ok(())
+}
+
+fn write_webp(image: &DynamicImage, path: &Path) -> Result<(), Box<dyn Error>> {
+    let rgba = image.to_rgba8();
+    let webp =
+        webp::Encoder::from_rgba(rgba.as_raw(), rgba.width(), rgba.height()).encode(WEBP_QUALITY);
+    std::fs::write(path, &*webp)?;
+    Ok(())
+}
+
+/// file names in IMAGES_DIR referenced by the html, used by the garbage collector
+pub fn referenced_images(html: &str) -> HashSet<String> {
+    let re = Regex::new(&format!(r#"/{}/([^"\s,/]+)"#, IMAGES_DIR)).unwrap();
+    re.captures_iter(html)
+        .map(|captures| captures[1].to_string())
+        .collect()
+}
+
+#[test]
+fn test_find_source() {
+    let input =
+        std::env::temp_dir().join(format!("pankat-test-find-source-{}", std::process::id()));
+    std::fs::create_dir_all(input.join("media")).unwrap();
+    std::fs::create_dir_all(input.join("posts/libnix")).unwrap();
+    std::fs::write(input.join("media/logo.png"), b"").unwrap();
+    std::fs::write(input.join("posts/libnix/shot.jpg"), b"").unwrap();
+    let input_dir = input.canonicalize().unwrap();
+
+    assert_eq!(
+        find_source(&input, "posts/about.mdwn", "media/logo.png"),
+        Some(input_dir.join("media/logo.png"))
+    );
+    assert_eq!(
+        find_source(&input, "posts/about.mdwn", "logo.png"),
+        Some(input_dir.join("media/logo.png"))
+    );
+    assert_eq!(
+        find_source(&input, "posts/libnix/libnix.mdwn", "shot.jpg"),
+        Some(input_dir.join("posts/libnix/shot.jpg"))
+    );
+    assert_eq!(find_source(&input, "posts/about.mdwn", "shot.jpg"), None);
+    assert_eq!(
+        find_source(&input, "posts/about.mdwn", "https://example.com/logo.png"),
+        None
+    );
+    assert_eq!(
+        find_source(&input, "posts/about.mdwn", "media/logo.svg"),
+        None
+    );
+
+    std::fs::remove_dir_all(&input).unwrap();
+}
+
+#[test]
+fn test_process() {
+    let dir = std::env::temp_dir().join(format!("pankat-test-process-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("wide.png");
+    image::RgbImage::from_pixel(1000, 500, image::Rgb([200, 10, 10]))
+        .save(&source)
+        .unwrap();
+
+    let output = dir.join("output");
+    let responsive_image = process(&source, &output).unwrap();
+    assert_eq!(
+        (responsive_image.width, responsive_image.height),
+        (1000, 500)
+    );
+    let widths: Vec<u32> = responsive_image.variants.iter().map(|(_, w)| *w).collect();
+    assert_eq!(widths, vec![480, 960, 1000]);
+    for (file_name, _) in responsive_image
+        .variants
+        .iter()
+        .chain(responsive_image.webp_variants.iter())
+    {
+        assert!(output.join(IMAGES_DIR).join(file_name).exists());
+    }
+    let (small, _) = &responsive_image.variants[0];
+    assert_eq!(
+        image::image_dimensions(output.join(IMAGES_DIR).join(small)).unwrap(),
+        (480, 240)
+    );
+
+    let html = responsive_image.to_html("/blog", r#"class="noFancy""#);
+    assert!(html.contains(r#"width="1000" height="500" loading="lazy" class="noFancy""#));
+    assert!(html.contains(r#"type="image/webp""#));
+    assert_eq!(
+        referenced_images(&html),
+        responsive_image
+            .variants
+            .iter()
+            .chain(responsive_image.webp_variants.iter())
+            .map(|(file_name, _)| file_name.clone())
+            .collect::<HashSet<String>>()
+    );
+
+    // a second run reuses the variants
+    assert_eq!(process(&source, &output).unwrap(), responsive_image);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
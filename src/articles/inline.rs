@@ -0,0 +1,276 @@
+use crate::articles::plugins::parse_key_value_arguments;
+use crate::articles::{links, utils, ArticleWithTags};
+use crate::config;
+use crate::db::links::LINK_KIND_INLINE;
+use crate::renderer::utils::date_and_time;
+use diesel::prelude::*;
+use regex::Regex;
+use std::collections::HashSet;
+use std::error::Error;
+
+/// the articles listed by `[[!inline tags=nix series=libnix limit=10]]`
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct InlineQuery {
+    pub tags: Vec<String>,
+    pub series: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl InlineQuery {
+    /// `tags` is a comma separated list, an article needs one of them
+    pub fn parse(input: &str) -> Result<InlineQuery, Box<dyn Error>> {
+        let arguments = parse_key_value_arguments("inline", input, &["tags", "series", "limit"])?;
+        let tags: Vec<String> = arguments
+            .get("tags")
+            .map(|tags| {
+                tags.split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let limit = match arguments.get("limit") {
+            Some(limit) => match limit.parse::<usize>() {
+                Ok(limit) if limit > 0 => Some(limit),
+                _ => {
+                    return Err(
+                        format!("inline: limit must be a positive number, got '{}'", limit).into(),
+                    )
+                }
+            },
+            None => None,
+        };
+        Ok(InlineQuery {
+            tags,
+            series: arguments.get("series").cloned(),
+            limit,
+        })
+    }
+
+    /// true if the article is listed, hidden articles and special pages never are
+    pub fn matches(&self, article: &ArticleWithTags) -> bool {
+        article.special_page != Some(true)
+            && !article.is_hidden(crate::db::article::now())
+            && (self.tags.is_empty()
+                || article
+                    .tags
+                    .iter()
+                    .flatten()
+                    .any(|tag| self.tags.contains(tag)))
+            && (self.series.is_none() || article.series == self.series)
+    }
+}
+
+impl std::fmt::Display for InlineQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut arguments: Vec<String> = Vec::new();
+        if !self.tags.is_empty() {
+            arguments.push(format!("tags=\"{}\"", self.tags.join(",")));
+        }
+        if let Some(series) = &self.series {
+            arguments.push(format!("series=\"{}\"", series));
+        }
+        if let Some(limit) = self.limit {
+            arguments.push(format!("limit={}", limit));
+        }
+        write!(f, "{}", arguments.join(" "))
+    }
+}
+
+/// `[[!inline]]` is rendered into this placeholder, the list is filled in when the article is
+/// written so it always shows the current articles
+pub fn inline_placeholder(query: &InlineQuery) -> String {
+    format!(
+        r#"<div class="pankat-inline" data-pankat-inline="{}"></div>"#,
+        utils::escape_html(&query.to_string())
+    )
+}
+
+fn placeholder_regex() -> Regex {
+    Regex::new(r#"<div class="pankat-inline" data-pankat-inline="([^"]*)">\s*</div>"#).unwrap()
+}
+
+/// all queries of the `[[!inline]]` directives in the html of an article
+pub fn inline_queries(html: &str) -> Vec<String> {
+    placeholder_regex()
+        .captures_iter(html)
+        .map(|c| utils::unescape_html(&c[1]))
+        .collect()
+}
+
+/// the listed articles, newest first
+fn query_articles(
+    conn: &mut SqliteConnection,
+    article: &ArticleWithTags,
+    query: &InlineQuery,
+) -> Result<Vec<ArticleWithTags>, diesel::result::Error> {
+    let mut articles: Vec<ArticleWithTags> = Vec::new();
+    if !query.tags.is_empty() {
+        for tag in &query.tags {
+            articles.extend(crate::db::article::get_visible_articles_by_tag(
+                conn,
+                tag.clone(),
+            )?);
+        }
+    } else if let Some(series) = &query.series {
+        articles = crate::db::article::get_visible_articles_by_series(conn, series)?;
+    } else {
+        articles = crate::db::article::get_visible_articles(conn)?;
+    }
+    let mut seen: HashSet<String> = HashSet::new();
+    articles.retain(|a| {
+        a.src_file_name != article.src_file_name
+            && query.matches(a)
+            && seen.insert(a.src_file_name.clone())
+    });
+    articles.sort_by_key(|a| std::cmp::Reverse(a.modification_date));
+    if let Some(limit) = query.limit {
+        articles.truncate(limit);
+    }
+    Ok(articles)
+}
+
+fn inline_html(subdir: &str, articles: &[ArticleWithTags]) -> String {
+    let mut html = String::from(r#"<div class="pankat-inline">"#);
+    for article in articles {
+        html.push_str(&format!(
+            r#"<div class="pankat-inline-article"><a href="{}/{}">{}</a> <span class="pankat-inline-date">{}</span>"#,
+            subdir,
+            article.dst_file_name,
+            utils::escape_html(article.title.as_deref().unwrap_or(&article.src_file_name)),
+            date_and_time(&article.modification_date)
+        ));
+        // like in the timeline the summary is used as it is
+        if let Some(summary) = &article.summary {
+            html.push_str(&format!(
+                r#"<div class="pankat-inline-summary">{}</div>"#,
+                summary
+            ));
+        }
+        html.push_str("</div>");
+    }
+    html.push_str("</div>");
+    html
+}
+
+/// replaces the `[[!inline]]` placeholders with the lists of matching articles
+pub fn resolve_inlines(
+    conn: &mut SqliteConnection,
+    article: &ArticleWithTags,
+    html: String,
+) -> String {
+    if inline_queries(&html).is_empty() {
+        return html;
+    }
+    let cfg = config::Config::get();
+    let subdir = cfg.subdir.display().to_string();
+    placeholder_regex()
+        .replace_all(&html, |captures: &regex::Captures| {
+            let query = match InlineQuery::parse(&utils::unescape_html(&captures[1])) {
+                Ok(query) => query,
+                Err(e) => {
+                    println!("Error: {}: {}", article.src_file_name, e);
+                    return String::new();
+                }
+            };
+            match query_articles(conn, article, &query) {
+                Ok(articles) => inline_html(&subdir, &articles),
+                Err(e) => {
+                    println!("Error: loading articles for [[!inline {}]]: {}", query, e);
+                    String::new()
+                }
+            }
+        })
+        .to_string()
+}
+
+/// true if the change of the article shows up in article lists
+pub fn listing_changed(old: Option<&ArticleWithTags>, new: &ArticleWithTags) -> bool {
+    let now = crate::db::article::now();
+    match old {
+        Some(old) => {
+            old.title != new.title
+                || old.dst_file_name != new.dst_file_name
+                || old.modification_date != new.modification_date
+                || old.summary != new.summary
+                || old.tags != new.tags
+                || old.series != new.series
+                || old.special_page != new.special_page
+                || old.is_hidden(now) != new.is_hidden(now)
+        }
+        None => true,
+    }
+}
+
+/// rewrites all articles with a `[[!inline]]` listing one of the given articles, used when an
+/// article is created, changed or removed, pass the old and the new version
+pub fn rebuild_articles_inlining(conn: &mut SqliteConnection, changed: &[ArticleWithTags]) {
+    let links = match crate::db::links::get_links_with_source(conn) {
+        Ok(links) => links,
+        Err(_) => return,
+    };
+    let changed_src_file_names: HashSet<&str> =
+        changed.iter().map(|a| a.src_file_name.as_str()).collect();
+    let affected: HashSet<String> = links
+        .into_iter()
+        .filter(|(link, _)| link.kind == LINK_KIND_INLINE)
+        .filter(|(link, _)| !changed_src_file_names.contains(link.src_file_name.as_str()))
+        .filter(|(link, _)| match InlineQuery::parse(&link.target) {
+            Ok(query) => changed.iter().any(|article| query.matches(article)),
+            Err(_) => false,
+        })
+        .map(|(link, _)| link.src_file_name)
+        .collect();
+    links::rewrite_articles(conn, affected, "an inlined article changed");
+}
+
+#[test]
+fn test_inline_query() {
+    let query = InlineQuery::parse("tags=nix,linux series=\"lib nix\" limit=10").unwrap();
+    assert_eq!(
+        query,
+        InlineQuery {
+            tags: vec!["nix".to_string(), "linux".to_string()],
+            series: Some("lib nix".to_string()),
+            limit: Some(10),
+        }
+    );
+    assert_eq!(InlineQuery::parse(&query.to_string()).unwrap(), query);
+    assert_eq!(InlineQuery::parse("").unwrap(), InlineQuery::default());
+    assert!(InlineQuery::parse("limit=0").is_err());
+    assert!(InlineQuery::parse("tag=nix").is_err());
+
+    let html = format!("<p>projects</p>\n{}", inline_placeholder(&query));
+    assert_eq!(inline_queries(&html), vec![query.to_string()]);
+    // pandoc turns the placeholder into a div of its own
+    assert_eq!(
+        inline_queries(
+            r#"<div class="pankat-inline" data-pankat-inline="limit=3">
+</div>"#
+        ),
+        vec!["limit=3".to_string()]
+    );
+
+    let mut article = ArticleWithTags {
+        src_file_name: "posts/libnix.mdwn".to_string(),
+        dst_file_name: "libnix.html".to_string(),
+        title: Some("libnix".to_string()),
+        summary: Some("nix on <b>windows</b>".to_string()),
+        series: Some("lib nix".to_string()),
+        tags: Some(vec!["nix".to_string()]),
+        ..Default::default()
+    };
+    assert!(query.matches(&article));
+    assert!(!InlineQuery::parse("tags=go").unwrap().matches(&article));
+    assert_eq!(
+        inline_html("/blog", std::slice::from_ref(&article)),
+        r#"<div class="pankat-inline"><div class="pankat-inline-article"><a href="/blog/libnix.html">libnix</a> <span class="pankat-inline-date"></span><div class="pankat-inline-summary">nix on <b>windows</b></div></div></div>"#
+    );
+
+    let old = article.clone();
+    assert!(!listing_changed(Some(&old), &article));
+    article.tags = Some(vec!["go".to_string()]);
+    assert!(listing_changed(Some(&old), &article));
+    article.draft = Some(true);
+    assert!(!query.matches(&article));
+}
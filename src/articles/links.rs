@@ -0,0 +1,435 @@
+use crate::articles::{utils, ArticleWithTags};
+use crate::config;
+use crate::db::links::{ArticleLink, LINK_KIND_DIRECTIVE, LINK_KIND_HTML, LINK_KIND_INLINE};
+use diesel::prelude::*;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+/// `[[!link target]]` is rendered into this placeholder, it is resolved when the article is
+/// written so links always point to the current `dst_file_name` of the target
+pub fn link_placeholder(target: &str) -> String {
+    let target = utils::escape_html(target);
+    format!(
+        r#"<a class="pankat-link" data-pankat-link="{}">{}</a>"#,
+        target, target
+    )
+}
+
+fn placeholder_regex() -> Regex {
+    Regex::new(r#"<a class="pankat-link" data-pankat-link="([^"]*)">[^<]*</a>"#).unwrap()
+}
+
+/// all link targets referenced from the html of an article
+pub fn link_targets(html: &str) -> Vec<String> {
+    placeholder_regex()
+        .captures_iter(html)
+        .map(|c| utils::unescape_html(&c[1]))
+        .collect()
+}
+
+/// a target is either the src_file_name (with or without `.mdwn`), the file stem or the title
+pub fn link_target_matches(target: &str, src_file_name: &str, title: Option<&str>) -> bool {
+    let target = target.trim();
+    if src_file_name == target || src_file_name == format!("{}.mdwn", target) {
+        return true;
+    }
+    if utils::article_src_file_name_to_title(&PathBuf::from(src_file_name))
+        == target.replace('_', " ")
+    {
+        return true;
+    }
+    title.is_some_and(|title| title.eq_ignore_ascii_case(target))
+}
+
+/// LIKE patterns for the `[[!link]]` targets which might point to the article, a superset of
+/// `link_target_matches`
+fn directive_target_patterns(article: &ArticleWithTags) -> Vec<String> {
+    let mut patterns: Vec<String> = vec![article.src_file_name.clone()];
+    if let Some(src_file_name) = article.src_file_name.strip_suffix(".mdwn") {
+        patterns.push(src_file_name.to_string());
+    }
+    // `_` matches the spaces and underscores of the file name
+    patterns.push(
+        utils::article_src_file_name_to_title(&PathBuf::from(&article.src_file_name))
+            .replace(' ', "_"),
+    );
+    if let Some(title) = &article.title {
+        patterns.push(title.clone());
+    }
+    patterns
+}
+
+/// LIKE patterns for the articles a `[[!link target]]` might point to, a superset of
+/// `link_target_matches`
+fn target_patterns(target: &str) -> Vec<String> {
+    let target = target.trim();
+    vec![
+        target.to_string(),
+        format!("{}.mdwn", target),
+        // `_` matches the spaces and underscores of the file name
+        format!("%{}.mdwn", target.replace(' ', "_")),
+    ]
+}
+
+fn find_target<'a>(target: &str, articles: &'a [ArticleWithTags]) -> Option<&'a ArticleWithTags> {
+    articles
+        .iter()
+        .find(|a| a.src_file_name == target || a.src_file_name == format!("{}.mdwn", target))
+        .or_else(|| {
+            articles
+                .iter()
+                .find(|a| link_target_matches(target, &a.src_file_name, a.title.as_deref()))
+        })
+}
+
+/// the line of the `[[!link target]]` directive in the article source, for warnings
+fn directive_line(src_file_name: &str, target: &str) -> Option<usize> {
+    let cfg = config::Config::get();
+    let source = std::fs::read_to_string(cfg.input.join(src_file_name)).ok()?;
+    let re = Regex::new(&format!(r"\[\[!link\s+{}\s*\]\]", regex::escape(target))).ok()?;
+    let position = re.find(&source)?.start();
+    utils::position_to_line_and_col_number(&source, position)
+        .ok()
+        .map(|(line, _)| line + 1)
+}
+
+/// replaces the link placeholders with links to the target articles
+pub fn resolve_links(
+    conn: &mut SqliteConnection,
+    article: &ArticleWithTags,
+    html: String,
+) -> String {
+    let targets = link_targets(&html);
+    if targets.is_empty() {
+        return html;
+    }
+    let cfg = config::Config::get();
+    let patterns: Vec<String> = targets.iter().flat_map(|t| target_patterns(t)).collect();
+    let articles: Vec<ArticleWithTags> =
+        match crate::db::article::get_visible_articles_like(conn, &patterns) {
+            Ok(articles) => articles,
+            Err(e) => {
+                println!("Error: loading articles to resolve links failed: {}", e);
+                Vec::new()
+            }
+        };
+
+    placeholder_regex()
+        .replace_all(&html, |captures: &regex::Captures| {
+            let target = utils::unescape_html(&captures[1]);
+            match find_target(&target, &articles) {
+                Some(target_article) => format!(
+                    r#"<a href="{}/{}">{}</a>"#,
+                    cfg.subdir.display(),
+                    target_article.dst_file_name,
+                    utils::escape_html(target_article.title.as_deref().unwrap_or(&target))
+                ),
+                None => {
+                    let location = match directive_line(&article.src_file_name, &target) {
+                        Some(line) => format!("{}:{}", article.src_file_name, line),
+                        None => article.src_file_name.clone(),
+                    };
+                    println!(
+                        "Warning: {}: [[!link {}]] target article not found",
+                        location, target
+                    );
+                    format!(
+                        r#"<span class="pankat-link-missing">{}</span>"#,
+                        utils::escape_html(&target)
+                    )
+                }
+            }
+        })
+        .to_string()
+}
+
+/// collects `[[!link]]` targets and relative links to `.html` files of the rendered article
+pub fn extract_links(src_file_name: &str, dst_file_name: &str, html: &str) -> Vec<ArticleLink> {
+    let cfg = config::Config::get();
+    extract_links_with_subdir(
+        src_file_name,
+        dst_file_name,
+        html,
+        &cfg.subdir.display().to_string(),
+    )
+}
+
+fn extract_links_with_subdir(
+    src_file_name: &str,
+    dst_file_name: &str,
+    html: &str,
+    subdir: &str,
+) -> Vec<ArticleLink> {
+    let mut links: Vec<ArticleLink> = Vec::new();
+    for target in link_targets(html) {
+        links.push(ArticleLink {
+            src_file_name: src_file_name.to_string(),
+            kind: LINK_KIND_DIRECTIVE.to_string(),
+            target: target.trim().to_string(),
+        });
+    }
+
+    for query in crate::articles::inline::inline_queries(html) {
+        links.push(ArticleLink {
+            src_file_name: src_file_name.to_string(),
+            kind: LINK_KIND_INLINE.to_string(),
+            target: query,
+        });
+    }
+
+    let re = Regex::new(r##"href="([^"#?]+\.html)(?:[#?][^"]*)?""##).unwrap();
+    for captures in re.captures_iter(html) {
+        let href = utils::unescape_html(&captures[1]);
+        if href.contains("://") || href.starts_with("//") || href.starts_with("mailto:") {
+            continue;
+        }
+        let path: String = match href.strip_prefix('/') {
+            Some(absolute) => {
+                let subdir = subdir.trim_matches('/');
+                match absolute.strip_prefix(subdir) {
+                    Some(rest) if !subdir.is_empty() => rest.trim_start_matches('/').to_string(),
+                    _ => absolute.to_string(),
+                }
+            }
+            None => {
+                let parent = Path::new(dst_file_name)
+                    .parent()
+                    .unwrap_or_else(|| Path::new(""));
+                normalize(&parent.join(&href))
+            }
+        };
+        if path.is_empty() || path == dst_file_name {
+            continue;
+        }
+        links.push(ArticleLink {
+            src_file_name: src_file_name.to_string(),
+            kind: LINK_KIND_HTML.to_string(),
+            target: path,
+        });
+    }
+    links.sort_by(|a, b| (&a.kind, &a.target).cmp(&(&b.kind, &b.target)));
+    links.dedup();
+    links
+}
+
+/// resolves `.` and `..` of a relative path
+fn normalize(path: &Path) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            _ => {}
+        }
+    }
+    parts.join("/")
+}
+
+/// true if the link points to the article
+pub fn link_matches(link: &ArticleLink, article: &ArticleWithTags) -> bool {
+    match link.kind.as_str() {
+        LINK_KIND_HTML => link.target == article.dst_file_name,
+        // lists articles, see `inline::rebuild_articles_inlining`
+        LINK_KIND_INLINE => false,
+        _ => link_target_matches(
+            &link.target,
+            &article.src_file_name,
+            article.title.as_deref(),
+        ),
+    }
+}
+
+/// visible articles and special pages linking to the article, sorted by date
+pub fn get_backlinks(
+    conn: &mut SqliteConnection,
+    article: &ArticleWithTags,
+) -> Vec<ArticleWithTags> {
+    let links = match crate::db::links::get_links_with_source_to(
+        conn,
+        &article.dst_file_name,
+        &directive_target_patterns(article),
+    ) {
+        Ok(links) => links,
+        Err(e) => {
+            println!("Error: loading links failed: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut backlinks: Vec<ArticleWithTags> = Vec::new();
+    for (link, source) in links {
+        if source.src_file_name == article.src_file_name || seen.contains(&source.src_file_name) {
+            continue;
+        }
+        if link_matches(&link, article) {
+            seen.insert(source.src_file_name.clone());
+            backlinks.push(source.into());
+        }
+    }
+    backlinks
+}
+
+pub fn rewrite_articles(
+    conn: &mut SqliteConnection,
+    src_file_names: HashSet<String>,
+    reason: &str,
+) {
+    for src_file_name in src_file_names {
+        if let Ok(Some(article)) =
+            crate::db::article::get_article_with_tags_by_src_file_name(conn, src_file_name)
+        {
+            if !article.is_hidden(crate::db::article::now()) {
+                println!("Rebuilding {} because {}", article.src_file_name, reason);
+                crate::articles::write_article_to_disk(conn, &article);
+            }
+        }
+    }
+}
+
+/// rewrites all articles with a `[[!link]]` pointing to one of the given articles, used when an
+/// article is created, renamed or removed
+pub fn rebuild_articles_linking_to(conn: &mut SqliteConnection, changed: &[ArticleWithTags]) {
+    let links = match crate::db::links::get_links_with_source(conn) {
+        Ok(links) => links,
+        Err(_) => return,
+    };
+    let changed_src_file_names: HashSet<&str> =
+        changed.iter().map(|a| a.src_file_name.as_str()).collect();
+    let affected: HashSet<String> = links
+        .into_iter()
+        .filter(|(link, _)| link.kind == LINK_KIND_DIRECTIVE)
+        .filter(|(link, _)| !changed_src_file_names.contains(link.src_file_name.as_str()))
+        .filter(|(link, _)| changed.iter().any(|article| link_matches(link, article)))
+        .map(|(link, _)| link.src_file_name)
+        .collect();
+    rewrite_articles(conn, affected, "a linked article changed");
+}
+
+/// rewrites the articles whose backlinks changed: targets of added and removed links or, if
+/// title, file name or visibility of the article changed, the targets of all its links
+pub fn rebuild_link_targets(
+    conn: &mut SqliteConnection,
+    article: &ArticleWithTags,
+    old_links: &[ArticleLink],
+    new_links: &[ArticleLink],
+    article_changed: bool,
+) {
+    let old: HashSet<&ArticleLink> = old_links.iter().collect();
+    let new: HashSet<&ArticleLink> = new_links.iter().collect();
+    let changed_links: Vec<&ArticleLink> = if article_changed {
+        old.union(&new).copied().collect()
+    } else {
+        old.symmetric_difference(&new).copied().collect()
+    };
+    if changed_links.is_empty() {
+        return;
+    }
+    let articles = match crate::db::article::get_all_articles(conn) {
+        Ok(articles) => articles,
+        Err(_) => return,
+    };
+    let targets: HashSet<String> = articles
+        .into_iter()
+        .filter(|target| target.src_file_name != article.src_file_name)
+        .filter(|target| changed_links.iter().any(|link| link_matches(link, target)))
+        .map(|target| target.src_file_name)
+        .collect();
+    rewrite_articles(conn, targets, "its backlinks changed");
+}
+
+#[test]
+fn test_link_targets() {
+    let html = format!(
+        "<p>see {} and {}</p>",
+        link_placeholder("libnix_mingw_status"),
+        link_placeholder("Nix \"on\" <Windows>")
+    );
+    assert_eq!(
+        link_targets(&html),
+        vec![
+            "libnix_mingw_status".to_string(),
+            "Nix \"on\" <Windows>".to_string()
+        ]
+    );
+    assert!(link_targets("<p>no links</p>").is_empty());
+}
+
+#[test]
+fn test_link_target_matches() {
+    let src = "posts/libnix/libnix_mingw_status.mdwn";
+    assert!(link_target_matches(src, src, None));
+    assert!(link_target_matches(
+        "posts/libnix/libnix_mingw_status",
+        src,
+        None
+    ));
+    assert!(link_target_matches("libnix_mingw_status", src, None));
+    assert!(link_target_matches("libnix mingw status", src, None));
+    assert!(link_target_matches(
+        "libnix on windows",
+        src,
+        Some("libnix on Windows")
+    ));
+    assert!(!link_target_matches(
+        "libnix",
+        src,
+        Some("libnix on Windows")
+    ));
+}
+
+#[test]
+fn test_target_patterns() {
+    assert_eq!(
+        target_patterns("libnix mingw status"),
+        vec![
+            "libnix mingw status",
+            "libnix mingw status.mdwn",
+            "%libnix_mingw_status.mdwn"
+        ]
+    );
+}
+
+#[test]
+fn test_directive_target_patterns() {
+    let article = ArticleWithTags {
+        src_file_name: "posts/libnix/libnix_mingw_status.mdwn".to_string(),
+        title: Some("libnix on Windows".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        directive_target_patterns(&article),
+        vec![
+            "posts/libnix/libnix_mingw_status.mdwn",
+            "posts/libnix/libnix_mingw_status",
+            "libnix_mingw_status",
+            "libnix on Windows"
+        ]
+    );
+}
+
+#[test]
+fn test_extract_links() {
+    let html = format!(
+        r#"<p>{} <a href="libnix_mingw_status.html#status">a</a>
+        <a href="/blog/about.html">b</a> <a href="../other/x.html">c</a>
+        <a href="https://example.com/y.html">d</a> <a href="media/z.jpg">e</a></p>"#,
+        link_placeholder("Nix on Windows")
+    );
+    let links = extract_links_with_subdir("posts/libnix/a.mdwn", "libnix/a.html", &html, "/blog");
+    let links: Vec<(String, String)> = links.into_iter().map(|l| (l.kind, l.target)).collect();
+    assert_eq!(
+        links,
+        vec![
+            ("directive".to_string(), "Nix on Windows".to_string()),
+            ("html".to_string(), "about.html".to_string()),
+            (
+                "html".to_string(),
+                "libnix/libnix_mingw_status.html".to_string()
+            ),
+            ("html".to_string(), "other/x.html".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,176 @@
+use regex::Regex;
+
+/// columns of leading whitespace, a tab counts as 4
+fn indentation(line: &str) -> usize {
+    let mut columns = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => columns += 1,
+            '\t' => columns += 4 - columns % 4,
+            _ => break,
+        }
+    }
+    columns
+}
+
+/// the fence character and length of a ```` ``` ```` or `~~~` line
+fn opening_fence(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start();
+    let c = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|x| *x == c).count();
+    // the info string of a backtick fence can't contain backticks
+    if len < 3 || (c == '`' && trimmed[len..].contains('`')) {
+        return None;
+    }
+    Some((c, len))
+}
+
+fn closes_fence(line: &str, fence: (char, usize)) -> bool {
+    let trimmed = line.trim();
+    indentation(line) < 4
+        && trimmed.chars().count() >= fence.1
+        && trimmed.chars().all(|c| c == fence.0)
+}
+
+/// byte ranges of backtick code spans in a run of prose lines
+fn code_spans(source: &str, start: usize, end: usize, ranges: &mut Vec<(usize, usize)>) {
+    let bytes = &source.as_bytes()[start..end];
+    let run = |i: usize| bytes[i..].iter().take_while(|b| **b == b'`').count();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => {
+                let len = run(i);
+                let mut j = i + len;
+                let mut close = None;
+                while j < bytes.len() {
+                    if bytes[j] == b'`' {
+                        if run(j) == len {
+                            close = Some(j);
+                            break;
+                        }
+                        j += run(j);
+                    } else {
+                        j += 1;
+                    }
+                }
+                match close {
+                    Some(j) => {
+                        ranges.push((start + i, start + j + len));
+                        i = j + len;
+                    }
+                    // an unmatched backtick run is literal text
+                    None => i += len,
+                }
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+/// byte ranges of fenced code blocks, indented code blocks and code spans, sorted by position
+///
+/// directives inside of them are not evaluated so posts can show the directive syntax.
+pub fn code_ranges(source: &str) -> Vec<(usize, usize)> {
+    let list_item = Regex::new(r"^([-*+]|\d+[.)])(\s|$)").unwrap();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut fence: Option<((char, usize), usize)> = None;
+    let mut indented: Option<(usize, usize)> = None;
+    let mut prose: Option<(usize, usize)> = None;
+    let mut previous_blank = true;
+    let mut in_list = false;
+
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+
+        if let Some((open, fence_start)) = fence {
+            if closes_fence(content, open) {
+                ranges.push((fence_start, offset));
+                fence = None;
+            }
+            continue;
+        }
+
+        let blank = content.trim().is_empty();
+        let indent = indentation(content);
+        let is_code = !blank
+            && indent >= 4
+            && !in_list
+            && (previous_blank || indented.is_some_and(|(_, end)| end == start));
+        let opens_fence = if indent < 4 {
+            opening_fence(content)
+        } else {
+            None
+        };
+
+        if blank || is_code || opens_fence.is_some() {
+            if let Some((prose_start, prose_end)) = prose.take() {
+                code_spans(source, prose_start, prose_end, &mut ranges);
+            }
+        }
+        if !is_code && !blank {
+            if let Some(range) = indented.take() {
+                ranges.push(range);
+            }
+        }
+
+        if let Some(open) = opens_fence {
+            fence = Some((open, start));
+        } else if is_code {
+            indented = Some((indented.map_or(start, |(s, _)| s), offset));
+        } else if !blank {
+            if indent < 4 {
+                if list_item.is_match(content.trim_start()) {
+                    in_list = true;
+                } else if previous_blank {
+                    in_list = false;
+                }
+            }
+            prose = Some((prose.map_or(start, |(s, _)| s), offset));
+        }
+        previous_blank = blank;
+    }
+
+    // an unclosed fence runs to the end of the document
+    if let Some((_, fence_start)) = fence {
+        ranges.push((fence_start, source.len()));
+    }
+    if let Some(range) = indented {
+        ranges.push(range);
+    }
+    if let Some((prose_start, prose_end)) = prose {
+        code_spans(source, prose_start, prose_end, &mut ranges);
+    }
+    ranges.sort();
+    ranges
+}
+
+#[test]
+fn test_code_ranges() {
+    let source =
+        "a `[[!tag x]]` b\n\n```markdown\n[[!tag y]]\n```\n\n    [[!tag z]]\n\nc ``x ` y`` d\n";
+    let ranges: Vec<&str> = code_ranges(source)
+        .into_iter()
+        .map(|(start, end)| &source[start..end])
+        .collect();
+    assert_eq!(
+        ranges,
+        vec![
+            "`[[!tag x]]`",
+            "```markdown\n[[!tag y]]\n```\n",
+            "    [[!tag z]]\n",
+            "``x ` y``"
+        ]
+    );
+
+    // list continuations and lazy lines are not code, unmatched backticks are literal
+    let source = "- item\n\n    [[!img a.png]]\n\nsome ` text\n  more\n";
+    assert_eq!(code_ranges(source), vec![]);
+
+    let source = "~~~~\n[[!tag a]]\n~~~\nstill code\n";
+    assert_eq!(code_ranges(source), vec![(0, source.len())]);
+}
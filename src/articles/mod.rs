@@ -2,32 +2,47 @@ use crate::db::article::{
     get_prev_and_next_article, get_prev_and_next_article_for_series, ArticleNeighbours,
 };
 use crate::db::cache::{compute_hash, get_cache, set_cache};
+use crate::db::links::ArticleLink;
 use crate::db::DbPool;
 use colored::Colorize;
 use notify::EventKind;
 use scopeguard::defer;
 
+use rayon::prelude::*;
 use regex::Regex;
 use serde_json::json;
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-mod plugins;
+mod diagrams;
+pub mod front_matter;
+mod images;
+pub mod inline;
+mod links;
+mod markdown;
+pub mod plugins;
+pub mod related;
 mod tests;
 pub mod timeline;
-mod utils;
+pub mod timings;
+pub mod utils;
+pub mod word_count;
 
 use crate::config;
+use crate::renderer::backend::{get_renderer, RenderOptions};
 use crate::renderer::html::{
     create_html_from_content_template, create_html_from_standalone_template_by_article,
     create_index_from_most_recent_article_template,
 };
-use crate::renderer::pandoc::pandoc_mdwn_2_html;
+use crate::renderer::katex::check_katex;
+use crate::renderer::pandoc::MATH_MODES;
 
-use self::plugins::{draft, img, meta, series, specialpage, summary, tag, title};
+use self::plugins::arguments::directive_len;
+use self::plugins::PluginRegistry;
+use self::timings::BuildTimings;
 use diesel::prelude::*;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub struct ArticleWithTags {
     pub id: Option<i32>,
     pub src_file_name: String,
@@ -42,10 +57,35 @@ pub struct ArticleWithTags {
     pub anchorjs: Option<bool>,
     pub tocify: Option<bool>,
     pub live_updates: Option<bool>,
+    pub numbered: Option<bool>,
+    pub author: Option<String>,
+    pub updated: Option<chrono::NaiveDateTime>,
+    pub lang: Option<String>,
+    pub canonical: Option<String>,
+    pub description: Option<String>,
+    pub keywords: Option<String>,
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    pub expire_at: Option<chrono::NaiveDateTime>,
+    pub math: Option<String>,
+    pub bibliography: Option<String>,
+    pub csl: Option<String>,
+    pub word_count: Option<i32>,
+    pub code_word_count: Option<i32>,
+    pub reading_time: Option<i32>,
+}
+
+impl ArticleWithTags {
+    /// drafts, articles with a future publish_at and expired ones are not published
+    pub fn is_hidden(&self, now: chrono::NaiveDateTime) -> bool {
+        self.draft == Some(true)
+            || self.publish_at.is_some_and(|publish_at| publish_at > now)
+            || self.expire_at.is_some_and(|expire_at| expire_at <= now)
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Insertable, AsChangeset)]
 #[diesel(table_name = crate::db::schema::articles)]
+#[diesel(treat_none_as_null = true)]
 pub struct NewArticle {
     pub src_file_name: String,
     pub dst_file_name: String,
@@ -58,6 +98,21 @@ pub struct NewArticle {
     pub anchorjs: Option<bool>,
     pub tocify: Option<bool>,
     pub live_updates: Option<bool>,
+    pub numbered: Option<bool>,
+    pub author: Option<String>,
+    pub updated: Option<chrono::NaiveDateTime>,
+    pub lang: Option<String>,
+    pub canonical: Option<String>,
+    pub description: Option<String>,
+    pub keywords: Option<String>,
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    pub expire_at: Option<chrono::NaiveDateTime>,
+    pub math: Option<String>,
+    pub bibliography: Option<String>,
+    pub csl: Option<String>,
+    pub word_count: Option<i32>,
+    pub code_word_count: Option<i32>,
+    pub reading_time: Option<i32>,
 }
 
 impl From<ArticleWithTags> for NewArticle {
@@ -74,6 +129,21 @@ impl From<ArticleWithTags> for NewArticle {
             anchorjs: article.anchorjs,
             tocify: article.tocify,
             live_updates: article.live_updates,
+            numbered: article.numbered,
+            author: article.author,
+            updated: article.updated,
+            lang: article.lang,
+            canonical: article.canonical,
+            description: article.description,
+            keywords: article.keywords,
+            publish_at: article.publish_at,
+            expire_at: article.expire_at,
+            math: article.math,
+            bibliography: article.bibliography,
+            csl: article.csl,
+            word_count: article.word_count,
+            code_word_count: article.code_word_count,
+            reading_time: article.reading_time,
         }
     }
 }
@@ -115,20 +185,71 @@ pub fn file_monitor_articles_change(
                 "📝 created / ✏️ modified called on {}",
                 event.path.display()
             );
+            let old_links: Vec<ArticleLink> =
+                crate::db::links::get_links(conn, event.path.display().to_string())
+                    .unwrap_or_default();
             match parse_article(conn, &event.path) {
                 Ok(article) => {
                     //println!("Parsed article: {:#?}", article);
+                    let old_article: Option<ArticleWithTags> =
+                        crate::db::article::get_article_with_tags_by_src_file_name(
+                            conn,
+                            article.src_file_name.clone(),
+                        )
+                        .unwrap_or_default();
                     let reply = crate::db::article::set(conn, &article);
+                    let now = crate::db::article::now();
                     match reply {
                         Ok(db_reply) => {
+                            crate::scheduler::reschedule();
+                            let related_changed =
+                                related::update_related_articles(conn, &article.src_file_name);
                             write_article_to_disk(conn, &db_reply.article);
+                            links::rewrite_articles(
+                                conn,
+                                related_changed,
+                                "its related articles changed",
+                            );
+                            // links to this article are resolved by src_file_name, dst_file_name or title
+                            let link_target_changed = match &old_article {
+                                Some(old_article) => {
+                                    old_article.title != db_reply.article.title
+                                        || old_article.dst_file_name
+                                            != db_reply.article.dst_file_name
+                                        || old_article.is_hidden(now)
+                                            != db_reply.article.is_hidden(now)
+                                }
+                                None => true,
+                            };
+                            if link_target_changed {
+                                let mut changed: Vec<ArticleWithTags> =
+                                    vec![db_reply.article.clone()];
+                                changed.extend(old_article.clone());
+                                links::rebuild_articles_linking_to(conn, &changed);
+                            }
+                            if inline::listing_changed(old_article.as_ref(), &db_reply.article) {
+                                let mut changed: Vec<ArticleWithTags> =
+                                    vec![db_reply.article.clone()];
+                                changed.extend(old_article.clone());
+                                inline::rebuild_articles_inlining(conn, &changed);
+                            }
+                            let new_links: Vec<ArticleLink> =
+                                crate::db::links::get_links(conn, article.src_file_name.clone())
+                                    .unwrap_or_default();
+                            links::rebuild_link_targets(
+                                conn,
+                                &db_reply.article,
+                                &old_links,
+                                &new_links,
+                                link_target_changed,
+                            );
                             if db_reply.most_recent_article_change.is_some() {
                                 update_most_recent_article(conn);
                             }
                             // FIXME: timeline? change on: nav, summary, date, title, tags, series
                             //let _ = crate::articles::timeline::update_timeline(&articles);
 
-                            if Some(true) == db_reply.article.draft {
+                            if db_reply.article.is_hidden(now) {
                                 let cfg = config::Config::get();
                                 let output_path: PathBuf = cfg.output.clone();
                                 let mut output_filename = output_path.clone();
@@ -172,8 +293,28 @@ pub fn file_monitor_articles_change(
         }
         EventKind::Remove(_) => {
             println!("🗑️ removed called on {}", event.path.display());
-            let res =
-                crate::db::article::del_by_src_file_name(conn, event.path.display().to_string());
+            let src_file_name: String = event.path.display().to_string();
+            let old_article: Option<ArticleWithTags> =
+                crate::db::article::get_article_with_tags_by_src_file_name(
+                    conn,
+                    src_file_name.clone(),
+                )
+                .unwrap_or_default();
+            let old_links: Vec<ArticleLink> =
+                crate::db::links::get_links(conn, src_file_name.clone()).unwrap_or_default();
+            let res = crate::db::article::del_by_src_file_name(conn, src_file_name.clone());
+            let _ = crate::db::links::del_links_by_src_file_name(conn, src_file_name.clone());
+            let _ = crate::db::dependencies::del_dependencies_by_src_file_name(
+                conn,
+                src_file_name.clone(),
+            );
+            if let Some(old_article) = old_article {
+                links::rebuild_articles_linking_to(conn, std::slice::from_ref(&old_article));
+                links::rebuild_link_targets(conn, &old_article, &old_links, &[], true);
+                inline::rebuild_articles_inlining(conn, std::slice::from_ref(&old_article));
+                let related_changed = related::update_related_articles(conn, &src_file_name);
+                links::rewrite_articles(conn, related_changed, "its related articles changed");
+            }
             match res {
                 // FIXME implement this
                 Ok(_) => Err("FIXME: implement this".to_string()),
@@ -218,7 +359,10 @@ pub fn collect_garbage(pool: &DbPool) {
                             .unwrap()
                             .to_path_buf();
                         let relative_entry_string: String = relative_entry.display().to_string();
-                        if relative_entry_string == PANKAT_FILE {
+                        if relative_entry_string == PANKAT_FILE
+                            || relative_entry_string == images::IMAGES_DIR
+                            || relative_entry_string == diagrams::DIAGRAMS_DIR
+                        {
                             continue;
                         }
                         if entry.file_type().unwrap().is_dir() {
@@ -244,10 +388,42 @@ pub fn collect_garbage(pool: &DbPool) {
         Err(_) => {}
     };
 
+    if let Ok(src_file_names) = crate::db::links::get_links_src_file_names(&mut conn) {
+        println!("====== Running GC on 'article_links table' ======");
+        for src_file_name in src_file_names {
+            let path = input_path.join(src_file_name.clone());
+            if !path.exists() {
+                println!("Removing garbage 'article_links table' entries: {:?}", path);
+                let _ = crate::db::links::del_links_by_src_file_name(&mut conn, src_file_name);
+            }
+        }
+    }
+
+    if let Ok(src_file_names) = crate::db::dependencies::get_dependencies_src_file_names(&mut conn)
+    {
+        println!("====== Running GC on 'article_dependencies table' ======");
+        for src_file_name in src_file_names {
+            let path = input_path.join(src_file_name.clone());
+            if !path.exists() {
+                println!(
+                    "Removing garbage 'article_dependencies table' entries: {:?}",
+                    path
+                );
+                let _ = crate::db::dependencies::del_dependencies_by_src_file_name(
+                    &mut conn,
+                    src_file_name,
+                );
+            }
+        }
+    }
+
     match crate::db::cache::get_cache_src_file_names(&mut conn) {
         Ok(entries) => {
             println!("====== Running GC on 'cache table' ======");
             for (id, path) in entries {
+                if path.starts_with(diagrams::DIAGRAM_CACHE_PREFIX) {
+                    continue;
+                }
                 let path = input_path.join(path);
                 if !path.exists() {
                     println!("Removing garbage 'cache table' entry: {:?}", path);
@@ -257,9 +433,59 @@ pub fn collect_garbage(pool: &DbPool) {
         }
         Err(_) => {}
     };
+
+    // image variants are only kept while an article in the cache references them
+    if let Ok(htmls) = crate::db::cache::get_cache_htmls(&mut conn) {
+        println!(
+            "====== Running GC on 'output/{}' directory ======",
+            images::IMAGES_DIR
+        );
+        let referenced: std::collections::HashSet<String> = htmls
+            .iter()
+            .flat_map(|html| images::referenced_images(html))
+            .collect();
+        if let Ok(entries) = std::fs::read_dir(output_path.join(images::IMAGES_DIR)) {
+            for entry in entries.flatten() {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                if !referenced.contains(&file_name) {
+                    println!("Removing garbage image variant: {:?}", entry.path());
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+
+        // diagrams as well, in the cache table and in the output
+        println!(
+            "====== Running GC on diagrams in 'cache table' and 'output/{}' ======",
+            diagrams::DIAGRAMS_DIR
+        );
+        let referenced: std::collections::HashSet<String> = htmls
+            .iter()
+            .flat_map(|html| diagrams::referenced_diagrams(html))
+            .collect();
+        if let Ok(entries) = crate::db::cache::get_cache_src_file_names(&mut conn) {
+            for (id, name) in entries {
+                if let Some(hash) = name.strip_prefix(diagrams::DIAGRAM_CACHE_PREFIX) {
+                    if !referenced.contains(&diagrams::file_name(hash)) {
+                        println!("Removing garbage 'cache table' diagram: {}", hash);
+                        let _ = crate::db::cache::del_cache_by_id(&mut conn, id.unwrap());
+                    }
+                }
+            }
+        }
+        if let Ok(entries) = std::fs::read_dir(output_path.join(diagrams::DIAGRAMS_DIR)) {
+            for entry in entries.flatten() {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                if !referenced.contains(&file_name) {
+                    println!("Removing garbage diagram: {:?}", entry.path());
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+    }
 }
 
-pub fn scan_articles(pool: &DbPool) {
+pub fn scan_articles(pool: &DbPool) -> BuildTimings {
     let cfg = config::Config::get();
     let input_path: PathBuf = cfg.input.clone();
 
@@ -272,9 +498,9 @@ pub fn scan_articles(pool: &DbPool) {
     println!("====== Parsing input for mdwn documents ======");
 
     fn traverse_and_collect_articles(
-        conn: &mut SqliteConnection,
         dir: &PathBuf,
         input_path: &PathBuf,
+        article_paths: &mut Vec<PathBuf>,
     ) {
         if dir.is_dir() {
             if let Ok(entries) = std::fs::read_dir(dir) {
@@ -283,18 +509,12 @@ pub fn scan_articles(pool: &DbPool) {
                         let path = entry.path();
                         //println!("{}", path.clone().display());
                         if path.is_dir() {
-                            traverse_and_collect_articles(conn, &path, &input_path);
+                            traverse_and_collect_articles(&path, input_path, article_paths);
                         } else if let Some(ext) = path.extension() {
                             if ext == "mdwn" {
                                 let article_path: PathBuf =
                                     path.strip_prefix(input_path).unwrap().to_path_buf();
-                                match parse_article(conn, &article_path) {
-                                    Ok(article) => {
-                                        //println!("Parsed article: {:#?}", article);
-                                        let _ = crate::db::article::set(conn, &article);
-                                    }
-                                    Err(_) => { /* Handle errors if necessary */ }
-                                }
+                                article_paths.push(article_path);
                             }
                         }
                     }
@@ -303,13 +523,86 @@ pub fn scan_articles(pool: &DbPool) {
         }
     }
 
-    traverse_and_collect_articles(&mut conn, &input_path, &input_path);
+    let mut article_paths: Vec<PathBuf> = Vec::new();
+    traverse_and_collect_articles(&input_path, &input_path, &mut article_paths);
+
+    // the plugins use the database, they run one article after the other
+    let mut timings = BuildTimings::default();
+    let mut prepared_articles: Vec<PreparedArticle> = Vec::new();
+    for article_path in article_paths.iter() {
+        let parse_start = std::time::Instant::now();
+        if let Ok(prepared) = prepare_article(&mut conn, article_path) {
+            timings.article(&prepared.article.src_file_name).parse = parse_start.elapsed();
+            prepared_articles.push(prepared);
+        }
+    }
+
+    // the renderer doesn't, the outdated articles are rendered in parallel
+    let render_count = prepared_articles.iter().filter(|p| p.renew_cache).count();
+    let rendered_count = std::sync::atomic::AtomicUsize::new(0);
+    let render_start = std::time::Instant::now();
+    let workers = match rayon::ThreadPoolBuilder::new()
+        .num_threads(cfg.jobs)
+        .thread_name(|i| format!("pankat-render-{}", i))
+        .build()
+    {
+        Ok(workers) => workers,
+        Err(e) => {
+            println!("Error: Creating the render workers failed: {}", e);
+            return timings;
+        }
+    };
+    println!(
+        "====== Rendering {} of {} articles with {} workers ======",
+        render_count,
+        prepared_articles.len(),
+        workers.current_num_threads()
+    );
+    // errors aren't Send, they cross the threads as strings
+    let rendered: Vec<Option<(Result<String, String>, std::time::Duration)>> =
+        workers.install(|| {
+            prepared_articles
+                .par_iter()
+                .map(|prepared| {
+                    if !prepared.renew_cache {
+                        return None;
+                    }
+                    let render_start = std::time::Instant::now();
+                    let html = prepared.render().map_err(|e| e.to_string());
+                    let duration = render_start.elapsed();
+                    let done =
+                        rendered_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+                    println!(
+                        "[{}/{}] Rendered {} in {:.2?}",
+                        done, render_count, prepared.article.src_file_name, duration
+                    );
+                    Some((html, duration))
+                })
+                .collect()
+        });
+    timings.render = render_start.elapsed();
+    timings.workers = workers.current_num_threads();
+
+    for (prepared, rendered) in prepared_articles.into_iter().zip(rendered) {
+        let html = rendered.map(|(html, duration)| {
+            timings.article(&prepared.article.src_file_name).render = Some(duration);
+            html.map_err(|e| e.into())
+        });
+        match finish_article(&mut conn, prepared, html) {
+            Ok(article) => {
+                //println!("Parsed article: {:#?}", article);
+                let _ = crate::db::article::set(&mut conn, &article);
+            }
+            Err(_) => { /* Handle errors if necessary */ }
+        }
+    }
 
     let duration = start_time.elapsed();
     println!("Time to scan input for articles: {:?}", duration);
+    timings
 }
 
-pub fn build_articles(pool: &DbPool) {
+pub fn build_articles(pool: &DbPool, timings: &mut BuildTimings) {
     let mut conn = pool
         .get()
         .expect("Failed to get a connection from the pool");
@@ -319,6 +612,7 @@ pub fn build_articles(pool: &DbPool) {
     match crate::db::article::get_visible_articles(&mut conn) {
         Ok(articles) => {
             let _ = crate::articles::timeline::update_timeline(&articles);
+            related::update_all_related_articles(&mut conn);
 
             for article in articles {
                 let article_id = article.id.unwrap();
@@ -327,17 +621,32 @@ pub fn build_articles(pool: &DbPool) {
                     article.clone().dst_file_name,
                     article_id
                 );
+                let write_start = std::time::Instant::now();
                 write_article_to_disk(&mut conn, &article);
+                timings.article(&article.src_file_name).write = Some(write_start.elapsed());
             }
         }
         Err(_) => { /* Handle errors if necessary */ }
     }
 
+    // articles which expired or became drafts while pankat wasn't running
+    if let Ok(hidden) = crate::db::article::get_drafts(&mut conn) {
+        let cfg = config::Config::get();
+        for article in hidden {
+            let output_filename = cfg.output.join(&article.dst_file_name);
+            if output_filename.exists() {
+                println!("Removing unpublished article {}", article.dst_file_name);
+                let _ = std::fs::remove_file(output_filename);
+            }
+        }
+    }
+
     update_special_pages(&mut conn);
     update_most_recent_article(&mut conn);
 
     let duration = start_time.elapsed();
     println!("Time to build articles: {:?}", duration);
+    timings.print_summary();
 }
 
 pub fn update_special_pages(conn: &mut SqliteConnection) {
@@ -380,6 +689,107 @@ pub fn update_most_recent_article(conn: &mut SqliteConnection) {
     };
 }
 
+/// the articles right before and after `article` in the date ordered `articles`, whether or not
+/// `article` itself is contained
+fn neighbours_by_date<'a>(
+    articles: &'a [ArticleWithTags],
+    article: &ArticleWithTags,
+) -> Vec<&'a ArticleWithTags> {
+    let key = |a: &ArticleWithTags| (a.modification_date.is_none(), a.modification_date);
+    let others: Vec<&ArticleWithTags> = articles
+        .iter()
+        .filter(|a| a.src_file_name != article.src_file_name)
+        .collect();
+    let position = others.iter().filter(|a| key(a) <= key(article)).count();
+    let mut neighbours: Vec<&ArticleWithTags> = Vec::new();
+    if position > 0 {
+        neighbours.push(others[position - 1]);
+    }
+    if let Some(next) = others.get(position) {
+        neighbours.push(next);
+    }
+    neighbours
+}
+
+/// rebuilds what changes when articles are published or expire in the interval (`from`, `to`]:
+/// the articles, their neighbours, articles linking to them, the timeline and index.html
+pub fn update_scheduled_articles(
+    conn: &mut SqliteConnection,
+    from: chrono::NaiveDateTime,
+    to: chrono::NaiveDateTime,
+) {
+    let changed: Vec<ArticleWithTags> =
+        match crate::db::article::get_articles_with_schedule_change(conn, from, to) {
+            Ok(changed) => changed,
+            Err(e) => {
+                println!("Error: loading scheduled articles failed: {}", e);
+                return;
+            }
+        };
+    if changed.is_empty() {
+        return;
+    }
+    let visible: Vec<ArticleWithTags> = match crate::db::article::get_visible_articles(conn) {
+        Ok(visible) => visible,
+        Err(e) => {
+            println!("Error: loading visible articles failed: {}", e);
+            return;
+        }
+    };
+
+    let cfg = config::Config::get();
+    let mut neighbours: std::collections::HashSet<String> = std::collections::HashSet::new();
+    for article in changed.iter() {
+        neighbours.extend(related::update_related_articles(
+            conn,
+            &article.src_file_name,
+        ));
+    }
+    for article in changed.iter() {
+        if article.is_hidden(to) {
+            println!("⏰ {} expired, removing it", article.src_file_name);
+            let _ = std::fs::remove_file(cfg.output.join(&article.dst_file_name));
+        } else {
+            println!("⏰ publishing {}", article.src_file_name);
+            write_article_to_disk(conn, article);
+        }
+        let series: Vec<ArticleWithTags> = visible
+            .iter()
+            .filter(|a| article.series.is_some() && a.series == article.series)
+            .cloned()
+            .collect();
+        for neighbour in neighbours_by_date(&visible, article)
+            .into_iter()
+            .chain(neighbours_by_date(&series, article))
+        {
+            neighbours.insert(neighbour.src_file_name.clone());
+        }
+        links::rebuild_articles_linking_to(conn, std::slice::from_ref(article));
+        inline::rebuild_articles_inlining(conn, std::slice::from_ref(article));
+        let article_links: Vec<ArticleLink> =
+            crate::db::links::get_links(conn, article.src_file_name.clone()).unwrap_or_default();
+        links::rebuild_link_targets(conn, article, &article_links, &article_links, true);
+    }
+
+    for article in visible.iter() {
+        if neighbours.contains(&article.src_file_name)
+            && !changed
+                .iter()
+                .any(|c| c.src_file_name == article.src_file_name)
+        {
+            println!(
+                "Rebuilding {}, a neighbour was (un)published",
+                article.src_file_name
+            );
+            write_article_to_disk(conn, article);
+        }
+    }
+    if let Err(e) = crate::articles::timeline::update_timeline(&visible) {
+        println!("Error: updating the timeline failed: {}", e);
+    }
+    update_most_recent_article(conn);
+}
+
 fn create_nav_content_template(
     conn: &mut SqliteConnection,
     article: &ArticleWithTags,
@@ -397,17 +807,32 @@ fn create_nav_content_template(
             Err(_) => ArticleNeighbours::new(),
         };
 
+    let html: String = links::resolve_links(conn, article, html);
+    let html: String = inline::resolve_inlines(conn, article, html);
+    let backlinks: Vec<ArticleWithTags> = links::get_backlinks(conn, article);
+    let related: Vec<ArticleWithTags> =
+        match crate::db::related::get_visible_related_articles(conn, &article.src_file_name) {
+            Ok(related) => related.into_iter().map(|a| a.into()).collect(),
+            Err(e) => {
+                println!("Error: loading related articles failed: {}", e);
+                Vec::new()
+            }
+        };
+
     let content: String = create_html_from_content_template(
         article.clone(),
         html,
         article_neighbours,
         article_series_neighbours,
+        backlinks,
+        related,
     )
     .unwrap();
     content
 }
 
-fn write_article_to_disk(conn: &mut SqliteConnection, article: &ArticleWithTags) {
+/// returns the content within the page, it is sent to the browsers showing the article
+fn write_article_to_disk(conn: &mut SqliteConnection, article: &ArticleWithTags) -> Option<String> {
     let cfg = config::Config::get();
     let output_path: PathBuf = cfg.output.clone();
 
@@ -415,21 +840,57 @@ fn write_article_to_disk(conn: &mut SqliteConnection, article: &ArticleWithTags)
         Some(cache_entry) => {
             let content: String = create_nav_content_template(conn, article, cache_entry.html);
             let standalone_html: String =
-                create_html_from_standalone_template_by_article(article.clone(), content).unwrap();
+                create_html_from_standalone_template_by_article(article.clone(), content.clone())
+                    .unwrap();
 
             let mut output_filename = output_path.clone();
             output_filename.push(article.dst_file_name.clone());
-            write_to_disk(&standalone_html, &output_filename)
+            write_to_disk(&standalone_html, &output_filename);
+            Some(content)
         }
         None => {
             println!(
                 "Error retrieving cache for path: {}",
                 &article.src_file_name
             );
+            None
         }
     }
 }
 
+/// writes all pages again after a template changed, returns the command for the browsers
+/// showing each article, `reload` if the page around the article changed
+pub fn rebuild_all_pages(conn: &mut SqliteConnection, reload: bool) -> Vec<(String, String)> {
+    let mut articles: Vec<ArticleWithTags> = Vec::new();
+    match crate::db::article::get_visible_articles(conn) {
+        Ok(visible) => {
+            if let Err(e) = crate::articles::timeline::update_timeline(&visible) {
+                println!("Error: updating the timeline failed: {}", e);
+            }
+            articles.extend(visible);
+        }
+        Err(e) => println!("Error: loading visible articles failed: {}", e),
+    }
+    match crate::db::article::get_special_pages(conn) {
+        Ok(special_pages) => articles.extend(special_pages),
+        Err(e) => println!("Error: loading special pages failed: {}", e),
+    }
+    let mut commands: Vec<(String, String)> = Vec::new();
+    for article in articles.iter() {
+        println!("Rewriting {}, a template changed", article.dst_file_name);
+        if let Some(content) = write_article_to_disk(conn, article) {
+            let command = if reload {
+                json!({ "reload": "" })
+            } else {
+                json!({ "update": content })
+            };
+            commands.push((article.src_file_name.clone(), command.to_string()));
+        }
+    }
+    update_most_recent_article(conn);
+    commands
+}
+
 pub fn write_to_disk(content: &String, filepath: &PathBuf) {
     if let Some(parent) = filepath.parent() {
         if let Err(e) = std::fs::create_dir_all(parent) {
@@ -439,10 +900,80 @@ pub fn write_to_disk(content: &String, filepath: &PathBuf) {
     std::fs::write(filepath, content.as_str()).expect("Unable to write HTML file");
 }
 
+/// an article after the plugins ran, the html is rendered from `markdown` unless the cache
+/// already has it
+struct PreparedArticle {
+    article: ArticleWithTags,
+    /// the source without the front matter, for the dependencies
+    source: String,
+    /// the source after the plugins ran, the renderer's input
+    markdown: String,
+    hash: String,
+    math: String,
+    citeproc: Vec<CiteprocFile>,
+    renew_cache: bool,
+}
+
+impl PreparedArticle {
+    fn render_options(&self) -> RenderOptions<'_> {
+        let citeproc_file = |name: &str| {
+            self.citeproc
+                .iter()
+                .find(|file| file.name == name)
+                .map(|file| file.path.as_path())
+        };
+        RenderOptions {
+            anchors: self.article.anchorjs.unwrap_or(true),
+            numbered: self.article.numbered.unwrap_or(true),
+            math: &self.math,
+            bibliography: citeproc_file("bibliography"),
+            csl: citeproc_file("csl"),
+        }
+    }
+
+    /// everything the html depends on: the markdown, the render options, the inputs of the
+    /// renderer like the pandoc version and the lua filters, and the content of the bibliography
+    /// and the citation style
+    fn cache_key(&self) -> Result<String, Box<dyn Error>> {
+        let cfg = config::Config::get();
+        let renderer = get_renderer(&cfg.renderer).map_err(|e| e.to_string())?;
+        let options = self.render_options();
+        Ok(format!(
+            "{}\nrenderer={}\n{:?}\n{}{}",
+            self.markdown,
+            renderer.name(),
+            options,
+            renderer.cache_key(&options)?,
+            self.citeproc
+                .iter()
+                .map(|file| format!("\n{}={}", file.name, file.hash))
+                .collect::<String>()
+        ))
+    }
+
+    /// runs the renderer, doesn't touch the database so articles can be rendered in parallel
+    fn render(&self) -> Result<String, Box<dyn Error>> {
+        let cfg = config::Config::get();
+        get_renderer(&cfg.renderer)
+            .map_err(|e| e.to_string().into())
+            .and_then(|renderer| renderer.render(&self.markdown, &self.render_options()))
+    }
+}
+
 fn parse_article(
     conn: &mut SqliteConnection,
     article_path: &PathBuf,
 ) -> Result<ArticleWithTags, Box<dyn Error>> {
+    let prepared = prepare_article(conn, article_path)?;
+    let html = prepared.renew_cache.then(|| prepared.render());
+    finish_article(conn, prepared, html)
+}
+
+/// evaluates the front matter and the plugins and computes the cache key
+fn prepare_article(
+    conn: &mut SqliteConnection,
+    article_path: &PathBuf,
+) -> Result<PreparedArticle, Box<dyn Error>> {
     let src_file_name_string = article_path.display().to_string();
     let cfg = config::Config::get();
     let input_path: PathBuf = cfg.input.clone();
@@ -453,74 +984,95 @@ fn parse_article(
     );
 
     let mut new_article: ArticleWithTags = ArticleWithTags {
-        id: None,
         src_file_name: src_file_name_string.clone(),
         dst_file_name: utils::create_dst_file_name(&article_path, cfg.flat),
-        title: None,
-        modification_date: None,
-        summary: None,
-        tags: None,
-        series: None,
-        draft: None,
-        special_page: None,
-        anchorjs: Some(true),
-        tocify: Some(true),
-        live_updates: Some(true),
+        ..Default::default()
     };
 
     let file_path: PathBuf = input_path.join(article_path);
     let article_mdwn_raw_string = std::fs::read_to_string(file_path).unwrap();
+    let (front_matter, article_mdwn_raw_string) =
+        match front_matter::split_front_matter(&article_mdwn_raw_string) {
+            Ok(res) => res,
+            Err(e) => {
+                println!(
+                    "Error: Parsing front matter of: {}: {}",
+                    src_file_name_string, e,
+                );
+                return Err(e);
+            }
+        };
+    diagrams::take_pending();
     match eval_plugins(&article_mdwn_raw_string, &mut new_article) {
         Ok(article_mdwn_refined_source) => {
-            if new_article.special_page == Some(true) {
-                new_article.tocify = None;
-            }
-            let hash: String = compute_hash(article_mdwn_refined_source.clone());
-            // println!(
-            //     "src_file_name_string.clone(): {}",
-            //     src_file_name_string.clone()
-            // );
-            let renew_cache: bool = match get_cache(conn, src_file_name_string.clone()) {
+            if let Err(e) = diagrams::publish(conn, &diagrams::take_pending(), &cfg.output) {
+                println!(
+                    "Error: Rendering diagrams of: {}: {}",
+                    src_file_name_string, e
+                );
+            }
+            if let Some(front_matter) = front_matter {
+                if let Err(e) = front_matter.apply(&mut new_article) {
+                    println!(
+                        "Error: Conflicting metadata in: {}: {}",
+                        src_file_name_string, e
+                    );
+                    return Err(e);
+                }
+            }
+            if let Err(e) = apply_default_options(&mut new_article, cfg) {
+                println!("Error: Invalid options in: {}: {}", src_file_name_string, e);
+                return Err(e);
+            }
+            let math: String = new_article.math.clone().unwrap_or_default();
+            let citeproc = match citeproc_files(&cfg.input, &new_article) {
+                Ok(citeproc) => citeproc,
+                Err(e) => {
+                    println!("Error: Bibliography of: {}: {}", src_file_name_string, e);
+                    return Err(e);
+                }
+            };
+            if new_article.title.is_none() {
+                let title = utils::article_src_file_name_to_title(article_path);
+                new_article.title = Some(title);
+            }
+            let mut prepared = PreparedArticle {
+                article: new_article,
+                source: article_mdwn_raw_string,
+                markdown: article_mdwn_refined_source,
+                hash: String::new(),
+                math,
+                citeproc,
+                renew_cache: true,
+            };
+            prepared.hash = match prepared.cache_key() {
+                Ok(cache_key) => compute_hash(cache_key),
+                Err(e) => {
+                    println!(
+                        "Error: Computing the cache key of: {}: {}",
+                        src_file_name_string, e
+                    );
+                    return Err(e);
+                }
+            };
+            // entries rendered by another pandoc, other lua filters or with other options are
+            // outdated, on startup all articles are checked so they are rendered again
+            prepared.renew_cache = match get_cache(conn, src_file_name_string.clone()) {
                 Some(cache_entry) => {
                     //println!("Cache_entry.hash: {}, hash: {}", cache_entry.hash, hash);
-                    if cache_entry.hash == hash {
+                    if cache_entry.hash == prepared.hash {
                         false
                     } else {
+                        println!(" ... cache outdated, rendering again");
                         true
                     }
                 }
                 None => true,
             };
-            if renew_cache {
-                //println!(" ... cache outdated, regenerating");
-                match pandoc_mdwn_2_html(
-                    article_mdwn_refined_source.clone(),
-                    new_article.anchorjs.unwrap_or(false),
-                ) {
-                    Ok(html) => {
-                        match set_cache(conn, src_file_name_string.clone(), html.clone(), hash) {
-                            Ok(_) => {}
-                            Err(e) => {
-                                println!("Error udpating cache: {}", e);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        println!(
-                            "Error: No entry in cache for path: {}: {}",
-                            src_file_name_string, e,
-                        );
-                        return Err(e);
-                    }
-                };
-            } else {
-                println!(" ... skipping call to pandoc, already in cache");
-            };
-            if new_article.title == None {
-                let title = utils::article_src_file_name_to_title(&article_path);
-                new_article.title = Some(title);
+            if !prepared.renew_cache {
+                println!(" ... skipping call to {}, already in cache", cfg.renderer);
             }
-            Ok(new_article)
+            Ok(prepared)
         }
         Err(e) => {
             println!(
@@ -532,81 +1084,326 @@ fn parse_article(
     }
 }
 
+/// caches the rendered `html`, `None` if it was cached already, and updates the links, the
+/// word count and the dependencies
+fn finish_article(
+    conn: &mut SqliteConnection,
+    prepared: PreparedArticle,
+    html: Option<Result<String, Box<dyn Error>>>,
+) -> Result<ArticleWithTags, Box<dyn Error>> {
+    let PreparedArticle {
+        article: mut new_article,
+        source: article_mdwn_raw_string,
+        hash,
+        ..
+    } = prepared;
+    let src_file_name_string = new_article.src_file_name.clone();
+    match html {
+        Some(Ok(html)) => match set_cache(conn, src_file_name_string.clone(), html, hash) {
+            Ok(_) => {}
+            Err(e) => {
+                println!("Error udpating cache: {}", e);
+            }
+        },
+        Some(Err(e)) => {
+            println!(
+                "Error: No entry in cache for path: {}: {}",
+                src_file_name_string, e,
+            );
+            return Err(e);
+        }
+        None => {}
+    }
+    if let Some(cache_entry) = get_cache(conn, src_file_name_string.clone()) {
+        let article_links: Vec<ArticleLink> = links::extract_links(
+            &new_article.src_file_name,
+            &new_article.dst_file_name,
+            &cache_entry.html,
+        );
+        if let Err(e) =
+            crate::db::links::set_links(conn, src_file_name_string.clone(), &article_links)
+        {
+            println!("Error updating links: {}", e);
+        }
+        let word_count = word_count::count_words(&cache_entry.html);
+        new_article.word_count = Some(word_count.words as i32);
+        new_article.code_word_count = Some(word_count.code_words as i32);
+        new_article.reading_time = Some(word_count.reading_time() as i32);
+    }
+    let dependencies: Vec<String> = eval_dependencies(&article_mdwn_raw_string, &new_article);
+    if let Err(e) =
+        crate::db::dependencies::set_dependencies(conn, src_file_name_string.clone(), &dependencies)
+    {
+        println!("Error updating dependencies: {}", e);
+    }
+    Ok(new_article)
+}
+
+/// options not set by `[[!options]]` are taken from the site wide defaults in pankat.toml
+fn apply_default_options(
+    article: &mut ArticleWithTags,
+    cfg: &config::Config,
+) -> Result<(), Box<dyn Error>> {
+    if article.special_page == Some(true) {
+        if article.tocify == Some(true) {
+            return Err(
+                "[[!options toc]] can't be used on a [[!specialpage]], it has no table of contents"
+                    .into(),
+            );
+        }
+        article.tocify = None;
+    } else {
+        article.tocify = article.tocify.or(Some(cfg.toc));
+    }
+    article.anchorjs = article.anchorjs.or(Some(cfg.anchors));
+    article.live_updates = article.live_updates.or(Some(cfg.live_updates));
+    article.numbered = article.numbered.or(Some(cfg.numbered));
+    let math = article.math.clone().unwrap_or_else(|| cfg.math.clone());
+    if !MATH_MODES.contains(&math.as_str()) {
+        return Err(format!(
+            "math = \"{}\" in pankat.toml is unknown, expected one of: {}",
+            math,
+            MATH_MODES.join(", ")
+        )
+        .into());
+    }
+    if math == "katex" {
+        check_katex().map_err(|e| format!("math=katex: {}", e))?;
+    }
+    article.math = Some(math);
+    article.bibliography = article.bibliography.clone().or(cfg.bibliography.clone());
+    if article.bibliography.is_some() {
+        article.csl = article.csl.clone().or(cfg.csl.clone());
+    }
+    Ok(())
+}
+
+/// byte ranges of the `[[!...]]` directives in the document
+///
+/// directives in code blocks and code spans and escaped ones, `\[[!tag foo]]`, are skipped and
+/// stay literal, pandoc drops the escaping backslash.
+fn find_directives(article_mdwn_raw_string: &str) -> Vec<(usize, usize)> {
+    let code_ranges = markdown::code_ranges(article_mdwn_raw_string);
+    let mut directives: Vec<(usize, usize)> = Vec::new();
+    let mut search = 0;
+    while let Some(offset) = article_mdwn_raw_string[search..].find("[[!") {
+        let start = search + offset;
+        if let Some((_, code_end)) = code_ranges
+            .iter()
+            .find(|(code_start, code_end)| *code_start <= start && start < *code_end)
+        {
+            search = *code_end;
+            continue;
+        }
+        if article_mdwn_raw_string[..start].ends_with('\\') {
+            search = start + 3;
+            continue;
+        }
+        match directive_len(&article_mdwn_raw_string[start..]) {
+            Some(len) => {
+                directives.push((start, start + len));
+                search = start + len;
+            }
+            None => search = start + 3,
+        }
+    }
+    directives
+}
+
+/// splits `[[!name arguments]]` into the name and the trimmed argument string
+fn split_directive(directive: &str) -> Option<(&str, &str)> {
+    let re = Regex::new(r#"(?s)^\[\[!([\w]+)(?:\s+(.*))?\]\]$"#).unwrap();
+    let captures = re.captures(directive)?;
+    let name: &str = captures.get(1).unwrap().as_str();
+    let argument = captures.get(2).map_or("", |m| m.as_str()).trim();
+    Some((name, argument))
+}
+
+/// the name of a closing `[[!/name]]` directive
+fn closing_directive(directive: &str) -> Option<&str> {
+    let re = Regex::new(r"^\[\[!/([\w]+)\s*\]\]$").unwrap();
+    re.captures(directive)
+        .map(|captures| captures.get(1).unwrap().as_str())
+}
+
+/// index of the `[[!/name]]` closing the block opened before `directives[from]`
+fn find_block_end(
+    source: &str,
+    directives: &[(usize, usize)],
+    from: usize,
+    name: &str,
+) -> Option<usize> {
+    let mut depth = 0;
+    for (index, (start, end)) in directives.iter().enumerate().skip(from) {
+        let directive = &source[*start..*end];
+        if closing_directive(directive).is_some_and(|n| n.eq_ignore_ascii_case(name)) {
+            if depth == 0 {
+                return Some(index);
+            }
+            depth -= 1;
+        } else if split_directive(directive).is_some_and(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            depth += 1;
+        }
+    }
+    None
+}
+
+fn report_plugin_error(source: &str, start: usize, article: &ArticleWithTags, e: Box<dyn Error>) {
+    match utils::position_to_line_and_col_number(source, start) {
+        Ok((line, col)) => {
+            println!(
+                "Error: call_plugin (at {}:{}:{}) returned error: {e}",
+                article.src_file_name, line, col
+            );
+        }
+        Err(_) => {
+            println!(
+                "Error: call_plugin (at {}:unknown position) returned error: {e}",
+                article.src_file_name
+            )
+        }
+    }
+}
+
 fn eval_plugins(
     article_mdwn_raw_string: &String,
     article: &mut ArticleWithTags,
 ) -> Result<String, Box<dyn Error>> {
-    let re = Regex::new(r"\[\[\!(.*?)\]\]").unwrap();
+    let directives = find_directives(article_mdwn_raw_string);
+    Ok(eval_directives(
+        article_mdwn_raw_string,
+        &directives,
+        (0, article_mdwn_raw_string.len()),
+        article,
+    ))
+}
 
-    let mut last = 0;
+/// evaluates the directives within `range` of the document, the body of a block directive is
+/// evaluated first and then passed to its plugin
+///
+/// directives returning an error are reported and left in the document as they are.
+fn eval_directives(
+    source: &str,
+    directives: &[(usize, usize)],
+    range: (usize, usize),
+    article: &mut ArticleWithTags,
+) -> String {
     let mut res: String = String::new();
-    for mat in re.find_iter(&article_mdwn_raw_string) {
-        let start = mat.start();
-        let end = mat.end();
+    let mut last = range.0;
+    let mut index = directives
+        .iter()
+        .position(|(start, _)| *start >= range.0)
+        .unwrap_or(directives.len());
+    while index < directives.len() && directives[index].1 <= range.1 {
+        let (start, end) = directives[index];
+        let directive = &source[start..end];
+        res += &source[last..start];
+        index += 1;
+        last = end;
 
-        if start > last {
-            res += &article_mdwn_raw_string[last..start];
-            last = start;
-        }
-
-        match exec_plugin(&article_mdwn_raw_string[start..end], article) {
-            Ok(result) => {
-                res.push_str(&result);
+        match split_directive(directive) {
+            Some((name, argument)) if PluginRegistry::get().is_block(name) => {
+                let close = match find_block_end(source, directives, index, name) {
+                    Some(close) => close,
+                    None => {
+                        res += directive;
+                        let e = format!("[[!{}]] is never closed by [[!/{}]]", name, name);
+                        report_plugin_error(source, start, article, e.into());
+                        continue;
+                    }
+                };
+                let (close_start, close_end) = directives[close];
+                let body = eval_directives(source, directives, (end, close_start), article);
+                match PluginRegistry::get().exec_block(name, argument, &body, article) {
+                    Ok(result) => res.push_str(&result),
+                    Err(e) => {
+                        res += directive;
+                        res += &body;
+                        res += &source[close_start..close_end];
+                        report_plugin_error(source, start, article, e);
+                    }
+                }
+                index = close + 1;
+                last = close_end;
             }
-            Err(e) => {
-                res += &article_mdwn_raw_string[start..end];
-                match utils::position_to_line_and_col_number(&article_mdwn_raw_string, start) {
-                    Ok((line, col)) => {
-                        println!(
-                            "Error: call_plugin (at {}:{}:{}) returned error: {e}",
-                            article.src_file_name, line, col
-                        );
+            _ => {
+                let result = match closing_directive(directive) {
+                    Some(name) => {
+                        Err(format!("[[!/{}]] closes a block which was never opened", name).into())
                     }
-                    Err(_) => {
-                        println!(
-                            "Error: call_plugin (at {}:unknown position) returned error: {e}",
-                            article.src_file_name
-                        )
+                    None => exec_plugin(directive, article),
+                };
+                match result {
+                    Ok(result) => res.push_str(&result),
+                    Err(e) => {
+                        res += directive;
+                        report_plugin_error(source, start, article, e);
                     }
                 }
             }
         }
-        if end <= article_mdwn_raw_string.len() {
-            res += &article_mdwn_raw_string[last..start];
-            last = end;
-        } else {
-            return Err(
-                "Error: The specified length to extract is beyond the string's bounds.".into(),
-            );
+    }
+    res += &source[last..range.1];
+    res
+}
+
+/// files and directories, relative to the input, the directives of the article depend on
+fn eval_dependencies(article_mdwn_raw_string: &str, article: &ArticleWithTags) -> Vec<String> {
+    let mut dependencies: Vec<String> = Vec::new();
+    for (start, end) in find_directives(article_mdwn_raw_string) {
+        let (name, argument) = match split_directive(&article_mdwn_raw_string[start..end]) {
+            Some(directive) => directive,
+            None => continue,
+        };
+        for path in PluginRegistry::get().dependencies(name, argument, article) {
+            let path = path.display().to_string();
+            if !dependencies.contains(&path) {
+                dependencies.push(path);
+            }
         }
     }
-    if last <= article_mdwn_raw_string.len() {
-        let t = &article_mdwn_raw_string[last..];
-        res += t;
+    // pandoc reads these, also when they are the site wide defaults from pankat.toml
+    for path in [&article.bibliography, &article.csl].into_iter().flatten() {
+        if !dependencies.contains(path) {
+            dependencies.push(path.clone());
+        }
     }
-    Ok(res)
+    dependencies
 }
 
-pub fn exec_plugin(input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
-    let pattern = r#"\[\[!([\w]+)(?:\s+(.*))?\]\]"#;
-    let re = Regex::new(pattern).unwrap();
-
-    if let Some(captures) = re.captures(input) {
-        let name: &str = captures.get(1).unwrap().as_str();
-        let argument = captures.get(2).map_or("", |m| m.as_str()).trim();
-
-        match name.to_lowercase().as_str() {
-            "title" => title::title(argument, article),
-            "specialpage" => specialpage::specialpage(argument, article),
-            "draft" => draft::draft(argument, article),
-            "meta" => meta::meta(argument, article),
-            "series" => series::series(argument, article),
-            "tag" => tag::tag(argument, article),
-            "img" => img::img(argument, article),
-            "summary" => summary::summary(argument, article),
-            _ => Err(format!("Plugin '{}' is not supported", name).into()),
+/// a file passed to pandoc's citeproc, the hash of its content is part of the cache key
+struct CiteprocFile {
+    name: &'static str,
+    path: PathBuf,
+    hash: String,
+}
+
+fn citeproc_files(
+    input: &Path,
+    article: &ArticleWithTags,
+) -> Result<Vec<CiteprocFile>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for (name, path) in [
+        ("bibliography", &article.bibliography),
+        ("csl", &article.csl),
+    ] {
+        if let Some(path) = path {
+            let path = input.join(path);
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("{}: can't read '{}': {}", name, path.display(), e))?;
+            files.push(CiteprocFile {
+                name,
+                path,
+                hash: compute_hash(content),
+            });
         }
-    } else {
-        Err("Plugin couldn't be decoded".into())
+    }
+    Ok(files)
+}
+
+pub fn exec_plugin(input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+    match split_directive(input) {
+        Some((name, argument)) => PluginRegistry::get().exec(name, argument, article),
+        None => Err("Plugin couldn't be decoded".into()),
     }
 }
@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// one argument of a directive, `foo.jpg` is positional, `alt="a cat"` is named
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Argument {
+    /// None for positional arguments
+    pub key: Option<String>,
+    pub value: String,
+    /// 1-based column of the argument in the argument string
+    pub column: usize,
+}
+
+/// the arguments of a directive in the order they were written
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Arguments(pub Vec<Argument>);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ArgumentError {
+    UnterminatedQuote {
+        column: usize,
+    },
+    UnterminatedTripleQuote {
+        column: usize,
+    },
+    MissingSeparator {
+        column: usize,
+    },
+    MissingKey {
+        column: usize,
+    },
+    DuplicateKey {
+        key: String,
+        column: usize,
+    },
+    UnknownKey {
+        key: String,
+        column: usize,
+        expected: String,
+    },
+    UnexpectedPositional {
+        value: String,
+        column: usize,
+    },
+}
+
+impl fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgumentError::UnterminatedQuote { column } => {
+                write!(f, "unterminated \" starting at column {}", column)
+            }
+            ArgumentError::UnterminatedTripleQuote { column } => {
+                write!(f, "unterminated \"\"\" starting at column {}", column)
+            }
+            ArgumentError::MissingSeparator { column } => {
+                write!(
+                    f,
+                    "expected whitespace after the quoted value at column {}",
+                    column
+                )
+            }
+            ArgumentError::MissingKey { column } => {
+                write!(f, "'=' without a key at column {}", column)
+            }
+            ArgumentError::DuplicateKey { key, column } => {
+                write!(
+                    f,
+                    "argument '{}' is given twice, again at column {}",
+                    key, column
+                )
+            }
+            ArgumentError::UnknownKey {
+                key,
+                column,
+                expected,
+            } => write!(
+                f,
+                "unknown argument '{}' at column {}, expected one of: {}",
+                key, column, expected
+            ),
+            ArgumentError::UnexpectedPositional { value, column } => {
+                write!(
+                    f,
+                    "expected key=value, got '{}' at column {}",
+                    value, column
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgumentError {}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// reads a bare, `"quoted"` or `"""triple quoted"""` value starting at `chars[*i]`
+fn parse_value(chars: &[char], i: &mut usize) -> Result<String, ArgumentError> {
+    let start = *i;
+    let value: String = if chars[start..].starts_with(&['"', '"', '"']) {
+        let content = start + 3;
+        let end = (content..chars.len())
+            .find(|j| chars[*j..].starts_with(&['"', '"', '"']))
+            .ok_or(ArgumentError::UnterminatedTripleQuote { column: start + 1 })?;
+        *i = end + 3;
+        chars[content..end].iter().collect()
+    } else if chars[start] == '"' {
+        let content = start + 1;
+        let end = (content..chars.len())
+            .find(|j| chars[*j] == '"')
+            .ok_or(ArgumentError::UnterminatedQuote { column: start + 1 })?;
+        *i = end + 1;
+        chars[content..end].iter().collect()
+    } else {
+        let end = (start..chars.len())
+            .find(|j| chars[*j].is_whitespace())
+            .unwrap_or(chars.len());
+        *i = end;
+        return Ok(chars[start..end].iter().collect());
+    };
+    if *i < chars.len() && !chars[*i].is_whitespace() {
+        return Err(ArgumentError::MissingSeparator { column: *i + 1 });
+    }
+    Ok(value)
+}
+
+/// parses ikiwiki style arguments: `foo.jpg alt="a cat on a mat" caption="""..."""`
+///
+/// values are bare words, `"quoted"` or `"""triple quoted"""`, the latter may contain quotes
+/// and newlines. directives taking free text, e.g. `[[!title]]`, get the raw argument string.
+pub fn parse_arguments(input: &str) -> Result<Arguments, ArgumentError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut arguments: Vec<Argument> = Vec::new();
+    let mut i = 0;
+    loop {
+        while i < chars.len() && chars[i].is_whitespace() {
+            i += 1;
+        }
+        if i >= chars.len() {
+            break;
+        }
+        let column = i + 1;
+        if chars[i] == '=' {
+            return Err(ArgumentError::MissingKey { column });
+        }
+        let key_end = (i..chars.len())
+            .find(|j| !is_key_char(chars[*j]))
+            .unwrap_or(chars.len());
+        let key: Option<String> = if key_end > i && key_end < chars.len() && chars[key_end] == '=' {
+            let key: String = chars[i..key_end].iter().collect();
+            if arguments.iter().any(|a| a.key.as_ref() == Some(&key)) {
+                return Err(ArgumentError::DuplicateKey { key, column });
+            }
+            i = key_end + 1;
+            Some(key)
+        } else {
+            None
+        };
+        let value = if i < chars.len() {
+            parse_value(&chars, &mut i)?
+        } else {
+            String::new()
+        };
+        arguments.push(Argument { key, value, column });
+    }
+    Ok(Arguments(arguments))
+}
+
+impl Arguments {
+    pub fn positional(&self) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter(|a| a.key.is_none())
+            .map(|a| a.value.as_str())
+            .collect()
+    }
+
+    pub fn named(&self) -> Vec<(&str, &str)> {
+        self.0
+            .iter()
+            .filter_map(|a| a.key.as_deref().map(|key| (key, a.value.as_str())))
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|a| a.key.as_deref() == Some(key))
+            .map(|a| a.value.as_str())
+    }
+
+    /// checks that at most `positional` positional arguments are used
+    pub fn expect_positional(&self, positional: usize) -> Result<(), ArgumentError> {
+        match self.0.iter().filter(|a| a.key.is_none()).nth(positional) {
+            Some(argument) => Err(ArgumentError::UnexpectedPositional {
+                value: argument.value.clone(),
+                column: argument.column,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// checks that only the given keys are used
+    pub fn expect_keys(&self, keys: &[&str]) -> Result<(), ArgumentError> {
+        for argument in self.0.iter() {
+            if let Some(key) = &argument.key {
+                if !keys.contains(&key.as_str()) {
+                    return Err(ArgumentError::UnknownKey {
+                        key: key.clone(),
+                        column: argument.column,
+                        expected: keys.join(", "),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn into_map(self) -> BTreeMap<String, String> {
+        self.0
+            .into_iter()
+            .filter_map(|a| a.key.map(|key| (key, a.value)))
+            .collect()
+    }
+}
+
+/// the length of the directive at the start of `source`, e.g. `[[!img "a ]]" alt=x]]`
+///
+/// `]]` inside quoted values does not end the directive, like in `parse_arguments` a quote only
+/// starts a value after whitespace or `=`, so `[[!title 12" vinyl]]` contains no quote.
+/// newlines are only allowed in triple quoted values, otherwise None is returned and the text
+/// is left as it is. a quote without a closing one on the same line, e.g.
+/// `[[!summary he said "hi]]`, is no quote either, the directive ends at the next `]]`.
+pub fn directive_len(source: &str) -> Option<usize> {
+    if !source.starts_with("[[!") {
+        return None;
+    }
+    let mut i = 3;
+    while i < source.len() {
+        let rest = &source[i..];
+        let starts_value = source[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_whitespace() || c == '=');
+        if rest.starts_with("]]") {
+            return Some(i + 2);
+        } else if let Some(quoted) = rest.strip_prefix("\"\"\"").filter(|_| starts_value) {
+            i += 3 + quoted.find("\"\"\"")? + 3;
+        } else if let Some(quoted) = rest.strip_prefix('"').filter(|_| starts_value) {
+            match quoted.find(['"', '\n']) {
+                Some(end) if quoted[end..].starts_with('"') => i += 1 + end + 1,
+                _ => {
+                    let line = rest.split('\n').next().unwrap_or_default();
+                    return line.find("]]").map(|end| i + end + 2);
+                }
+            }
+        } else if rest.starts_with('\n') {
+            return None;
+        } else {
+            i += rest.chars().next().map_or(1, |c| c.len_utf8());
+        }
+    }
+    None
+}
+
+#[test]
+fn test_parse_arguments() {
+    let arguments = parse_arguments(r#"foo.jpg alt="a cat on a mat" size=300x"#).unwrap();
+    assert_eq!(arguments.positional(), vec!["foo.jpg"]);
+    assert_eq!(
+        arguments.named(),
+        vec![("alt", "a cat on a mat"), ("size", "300x")]
+    );
+    assert_eq!(arguments.0[1].column, 9);
+
+    let arguments = parse_arguments("text=\"\"\"a \"quoted\"\nline\"\"\" empty= x").unwrap();
+    assert_eq!(arguments.get("text"), Some("a \"quoted\"\nline"));
+    assert_eq!(arguments.get("empty"), Some(""));
+    assert_eq!(arguments.positional(), vec!["x"]);
+    assert_eq!(parse_arguments("  ").unwrap(), Arguments::default());
+
+    assert_eq!(
+        parse_arguments(r#"foo alt="a cat"#).unwrap_err(),
+        ArgumentError::UnterminatedQuote { column: 9 }
+    );
+    assert_eq!(
+        parse_arguments(r#"alt="""a cat"#).unwrap_err(),
+        ArgumentError::UnterminatedTripleQuote { column: 5 }
+    );
+    assert_eq!(
+        parse_arguments(r#"alt="a"b"#).unwrap_err(),
+        ArgumentError::MissingSeparator { column: 8 }
+    );
+    assert_eq!(
+        parse_arguments("a =b").unwrap_err(),
+        ArgumentError::MissingKey { column: 3 }
+    );
+    assert_eq!(
+        parse_arguments("a=1 a=2").unwrap_err().to_string(),
+        "argument 'a' is given twice, again at column 5"
+    );
+
+    let arguments = parse_arguments("dir=cats foo=bar").unwrap();
+    assert_eq!(
+        arguments.expect_keys(&["dir"]).unwrap_err().to_string(),
+        "unknown argument 'foo' at column 10, expected one of: dir"
+    );
+    assert_eq!(
+        parse_arguments("a b")
+            .unwrap()
+            .expect_positional(1)
+            .unwrap_err(),
+        ArgumentError::UnexpectedPositional {
+            value: "b".to_string(),
+            column: 3
+        }
+    );
+}
+
+#[test]
+fn test_directive_len() {
+    assert_eq!(directive_len("[[!tag nix]] rest"), Some(12));
+    assert_eq!(directive_len(r#"[[!img a.jpg alt="x ]] y"]]"#), Some(27));
+    assert_eq!(directive_len("[[!x text=\"\"\"a\n]]\n\"\"\"]]"), Some(23));
+    assert_eq!(directive_len("[[!tag nix\n]]"), None);
+    assert_eq!(directive_len("[[!img alt=\"a\nb\"]]"), None);
+    assert_eq!(
+        directive_len(r#"[[!title 12" vinyl record]] rest"#),
+        Some(27)
+    );
+    assert_eq!(directive_len(r#"[[!summary he said "hi]]"#), Some(24));
+    assert_eq!(directive_len("[[!summary he said \"hi\n]]"), None);
+    assert_eq!(directive_len("[[!tag nix"), None);
+    assert_eq!(directive_len("[[tag]]"), None);
+}
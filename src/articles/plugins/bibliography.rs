@@ -0,0 +1,182 @@
+use crate::articles::plugins::arguments::parse_arguments;
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
+use crate::articles::utils;
+use crate::articles::ArticleWithTags;
+use crate::config;
+use std::error::Error;
+use std::path::Path;
+
+/// `[[!bibliography refs.bib style=ieee.csl]]` resolves `[@key]` citations with pandoc's
+/// citeproc, the reference list is shown by the content template below the article
+///
+/// paths are looked up like `[[!include]]`, so the files can be kept next to the article.
+/// without `style` the `csl` from pankat.toml or pandoc's chicago author-date style is used.
+pub struct Bibliography;
+
+/// the `.bib` file and the optional `style`
+fn bibliography_arguments(input: &str) -> Result<(String, Option<String>), Box<dyn Error>> {
+    let arguments = parse_arguments(input).map_err(|e| format!("bibliography: {}", e))?;
+    arguments
+        .expect_positional(1)
+        .and_then(|_| arguments.expect_keys(&["style"]))
+        .map_err(|e| format!("bibliography: {}", e))?;
+    match arguments.positional().first() {
+        Some(path) => Ok((
+            path.to_string(),
+            arguments.get("style").map(|style| style.to_string()),
+        )),
+        None => Err("bibliography: argument 'path' is missing".into()),
+    }
+}
+
+/// the file relative to the input, pandoc is called with the input joined to it
+fn find_file(input: &Path, src_file_name: &str, path: &str) -> Result<String, Box<dyn Error>> {
+    utils::find_input_path(input, src_file_name, path)
+        .filter(|p| p.is_file())
+        .map(|p| utils::relative_to_input(input, &p).display().to_string())
+        .ok_or_else(|| format!("bibliography: file '{}' not found", path).into())
+}
+
+fn set(field: &mut Option<String>, value: String) -> Result<(), Box<dyn Error>> {
+    match field {
+        Some(previous) if *previous != value => Err(format!(
+            "bibliography: already set to '{}' by another [[!bibliography]] directive",
+            previous
+        )
+        .into()),
+        _ => {
+            *field = Some(value);
+            Ok(())
+        }
+    }
+}
+
+fn bibliography(
+    input: &Path,
+    arguments: &str,
+    article: &mut ArticleWithTags,
+) -> Result<String, Box<dyn Error>> {
+    let (path, style) = bibliography_arguments(arguments)?;
+    let bibliography = find_file(input, &article.src_file_name, &path)?;
+    set(&mut article.bibliography, bibliography)?;
+    if let Some(style) = style {
+        let csl = find_file(input, &article.src_file_name, &style)?;
+        set(&mut article.csl, csl)?;
+    }
+    Ok("".to_string())
+}
+
+/// moves the reference list pandoc appends, `<div id="refs" ...>`, out of the article html so
+/// the content template can place it, returns the html without it and the reference list
+pub fn split_references(html: &str) -> (String, Option<String>) {
+    let Some(start) = html.find("<div id=\"refs\"") else {
+        return (html.to_string(), None);
+    };
+    let mut depth = 0;
+    let mut i = start;
+    while let Some(offset) = html[i..].find("div") {
+        let tag = i + offset;
+        if html[..tag].ends_with("</") {
+            depth -= 1;
+        } else if html[..tag].ends_with('<') {
+            depth += 1;
+        }
+        i = tag + 3;
+        if depth == 0 {
+            let end = html[i..].find('>').map_or(html.len(), |gt| i + gt + 1);
+            let references = html[start..end].to_string();
+            return (
+                format!("{}{}", &html[..start], &html[end..]),
+                Some(references),
+            );
+        }
+    }
+    (html.to_string(), None)
+}
+
+impl Plugin for Bibliography {
+    fn name(&self) -> &str {
+        "bibliography"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[
+            PluginArgument {
+                name: "path",
+                description: "the BibTeX or CSL JSON file with the references, e.g. refs.bib",
+                required: true,
+            },
+            PluginArgument {
+                name: "style",
+                description: "the citation style, e.g. style=ieee.csl",
+                required: false,
+            },
+        ]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        let cfg = config::Config::try_get().ok_or("bibliography: config is not initialized")?;
+        bibliography(&cfg.input, input, article)
+    }
+}
+
+#[test]
+fn test_bibliography() {
+    let input =
+        std::env::temp_dir().join(format!("pankat-test-bibliography-{}", std::process::id()));
+    std::fs::create_dir_all(input.join("posts")).unwrap();
+    std::fs::write(input.join("posts/refs.bib"), "@book{knuth84, title={TeX}}").unwrap();
+    std::fs::write(input.join("ieee.csl"), "<style/>").unwrap();
+
+    let mut article = ArticleWithTags {
+        src_file_name: "posts/citing.mdwn".to_string(),
+        dst_file_name: String::new(),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        bibliography(&input, "refs.bib style=ieee.csl", &mut article).unwrap(),
+        ""
+    );
+    assert_eq!(article.bibliography.as_deref(), Some("posts/refs.bib"));
+    assert_eq!(article.csl.as_deref(), Some("ieee.csl"));
+
+    assert!(bibliography(&input, "missing.bib", &mut article)
+        .unwrap_err()
+        .to_string()
+        .contains("file 'missing.bib' not found"));
+    assert!(bibliography(&input, "refs.bib lang=de", &mut article).is_err());
+
+    std::fs::write(input.join("other.bib"), "").unwrap();
+    assert_eq!(
+        bibliography(&input, "other.bib", &mut article)
+            .unwrap_err()
+            .to_string(),
+        "bibliography: already set to 'posts/refs.bib' by another [[!bibliography]] directive"
+    );
+
+    std::fs::remove_dir_all(&input).unwrap();
+}
+
+#[test]
+fn test_split_references() {
+    let html = "<p>see <a href=\"#ref-k\">(A 2020)</a></p>\n<div id=\"refs\" class=\"references csl-bib-body\" role=\"list\">\n<div id=\"ref-k\" class=\"csl-entry\" role=\"listitem\">\nA. 2020. <span>T.</span>\n</div>\n</div>\n<p>after</p>\n";
+    let (html, references) = split_references(html);
+    assert_eq!(
+        html,
+        "<p>see <a href=\"#ref-k\">(A 2020)</a></p>\n\n<p>after</p>\n"
+    );
+    assert_eq!(
+        references.as_deref(),
+        Some("<div id=\"refs\" class=\"references csl-bib-body\" role=\"list\">\n<div id=\"ref-k\" class=\"csl-entry\" role=\"listitem\">\nA. 2020. <span>T.</span>\n</div>\n</div>")
+    );
+
+    assert_eq!(
+        split_references("<p>no citations</p>"),
+        ("<p>no citations</p>".to_string(), None)
+    );
+}
@@ -0,0 +1,283 @@
+use crate::articles::plugins::{parse_key_value_arguments, Plugin, PluginArgument, PluginOutput};
+use crate::articles::utils;
+use crate::articles::ArticleWithTags;
+use crate::config;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// `[[!code file=src/main.rs lines=10-40 lang=rust]]` embeds an excerpt of a source file
+///
+/// instead of `lines` a `region=name` selects the lines between `ANCHOR: name` and
+/// `ANCHOR_END: name` comments, the marker lines are not shown.
+pub struct Code;
+
+const KEYS: [&str; 4] = ["file", "lines", "region", "lang"];
+
+fn lang_from_extension(path: &Path) -> String {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "rs" => "rust",
+        "py" => "python",
+        "js" => "javascript",
+        "ts" => "typescript",
+        "sh" => "bash",
+        "h" => "c",
+        "hpp" | "cc" => "cpp",
+        "hs" => "haskell",
+        "yml" => "yaml",
+        "md" | "mdwn" => "markdown",
+        ext => ext,
+    }
+    .to_string()
+}
+
+/// parses `10-40`, `10-` or `10` into a 1-based inclusive range
+fn parse_lines(lines: &str, line_count: usize) -> Result<(usize, usize), Box<dyn Error>> {
+    let invalid = || format!("code: lines must look like 10-40, got '{}'", lines);
+    let (start, end) = match lines.split_once('-') {
+        Some((start, "")) => (
+            start.trim().parse::<usize>().map_err(|_| invalid())?,
+            line_count,
+        ),
+        Some((start, end)) => (
+            start.trim().parse::<usize>().map_err(|_| invalid())?,
+            end.trim().parse::<usize>().map_err(|_| invalid())?,
+        ),
+        None => {
+            let line = lines.trim().parse::<usize>().map_err(|_| invalid())?;
+            (line, line)
+        }
+    };
+    if start == 0 || start > end || end > line_count {
+        return Err(format!(
+            "code: lines {} are out of range, the file has {} lines",
+            lines, line_count
+        )
+        .into());
+    }
+    Ok((start, end))
+}
+
+fn is_anchor(line: &str) -> bool {
+    line.contains("ANCHOR: ") || line.contains("ANCHOR_END: ")
+}
+
+/// the lines between the `ANCHOR: name` and `ANCHOR_END: name` markers
+fn region(content: &str, name: &str) -> Result<(usize, Vec<String>), Box<dyn Error>> {
+    let begin = format!("ANCHOR: {}", name);
+    let end = format!("ANCHOR_END: {}", name);
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.trim_end().ends_with(&begin))
+        .ok_or_else(|| format!("code: region '{}' not found", name))?;
+    let stop = lines[start..]
+        .iter()
+        .position(|line| line.trim_end().ends_with(&end))
+        .map(|p| p + start)
+        .ok_or_else(|| format!("code: region '{}' has no ANCHOR_END", name))?;
+    let region: Vec<String> = lines[start + 1..stop]
+        .iter()
+        .filter(|line| !is_anchor(line))
+        .map(|line| line.to_string())
+        .collect();
+    Ok((start + 2, region))
+}
+
+fn code(
+    input: &Path,
+    src_file_name: &str,
+    subdir: &str,
+    arguments: &str,
+) -> Result<String, Box<dyn Error>> {
+    let arguments = parse_key_value_arguments("code", arguments, &KEYS)?;
+    let file_argument = arguments
+        .get("file")
+        .ok_or("code: argument 'file' is missing")?;
+    let path = utils::find_input_path(input, src_file_name, file_argument)
+        .filter(|p| p.is_file())
+        .ok_or_else(|| format!("code: file '{}' not found", file_argument))?;
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("code: can't read '{}': {}", path.display(), e))?;
+    let file_name = utils::relative_to_input(input, &path).display().to_string();
+
+    let (first_line, selected, selection): (usize, Vec<String>, String) =
+        match (arguments.get("lines"), arguments.get("region")) {
+            (Some(_), Some(_)) => return Err("code: use either 'lines' or 'region'".into()),
+            (Some(lines), None) => {
+                let all: Vec<&str> = content.lines().collect();
+                let (start, end) = parse_lines(lines, all.len())?;
+                let selected = all[start - 1..end].iter().map(|l| l.to_string()).collect();
+                (start, selected, format!(", lines {}-{}", start, end))
+            }
+            (None, Some(name)) => {
+                let (start, selected) = region(&content, name)?;
+                (start, selected, format!(", region {}", name))
+            }
+            (None, None) => (
+                1,
+                content.lines().map(|l| l.to_string()).collect(),
+                String::new(),
+            ),
+        };
+    let lang = arguments
+        .get("lang")
+        .cloned()
+        .unwrap_or_else(|| lang_from_extension(&path));
+
+    // the fence has to be longer than any backtick run in the code
+    let mut fence = "```".to_string();
+    while selected.iter().any(|line| line.contains(&fence)) {
+        fence.push('`');
+    }
+    // files in posts/ and media/ are served by serve_input
+    let caption = if file_name.starts_with("posts/") || file_name.starts_with("media/") {
+        format!(
+            r#"<a href="{}/{}">{}</a>"#,
+            subdir,
+            file_name,
+            utils::escape_html(&file_name)
+        )
+    } else {
+        utils::escape_html(&file_name)
+    };
+    let mut classes = String::new();
+    if !lang.is_empty() {
+        classes += &format!(".{} ", lang);
+    }
+    Ok(format!(
+        "\n<div class=\"pankat-code-caption\">{}{}</div>\n\n{} {{{}.numberLines startFrom=\"{}\"}}\n{}\n{}\n",
+        caption,
+        selection,
+        fence,
+        classes,
+        first_line,
+        selected.join("\n"),
+        fence
+    ))
+}
+
+impl Plugin for Code {
+    fn name(&self) -> &str {
+        "code"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[
+            PluginArgument {
+                name: "file",
+                description: "the source file, e.g. file=posts/libnix/main.rs",
+                required: true,
+            },
+            PluginArgument {
+                name: "lines",
+                description: "the lines to show, e.g. lines=10-40",
+                required: false,
+            },
+            PluginArgument {
+                name: "region",
+                description: "the lines between ANCHOR: name and ANCHOR_END: name",
+                required: false,
+            },
+            PluginArgument {
+                name: "lang",
+                description: "the language for highlighting, defaults to the file extension",
+                required: false,
+            },
+        ]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Markdown
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        let cfg = config::Config::try_get().ok_or("code: config is not initialized")?;
+        code(
+            &cfg.input,
+            &article.src_file_name,
+            &cfg.subdir.display().to_string(),
+            input,
+        )
+    }
+
+    fn dependencies(&self, input: &str, article: &ArticleWithTags) -> Vec<PathBuf> {
+        let file = match parse_key_value_arguments("code", input, &KEYS) {
+            Ok(arguments) => arguments.get("file").cloned().unwrap_or_default(),
+            Err(_) => return Vec::new(),
+        };
+        match config::Config::try_get() {
+            Some(cfg) => utils::find_input_path(&cfg.input, &article.src_file_name, &file)
+                .filter(|p| p.is_file())
+                .map(|path| utils::relative_to_input(&cfg.input, &path))
+                .into_iter()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[test]
+fn test_parse_lines() {
+    assert_eq!(parse_lines("10-40", 50).unwrap(), (10, 40));
+    assert_eq!(parse_lines("10-", 50).unwrap(), (10, 50));
+    assert_eq!(parse_lines("7", 50).unwrap(), (7, 7));
+    assert!(parse_lines("10-60", 50).is_err());
+    assert!(parse_lines("0-3", 50).is_err());
+    assert!(parse_lines("a-b", 50).is_err());
+}
+
+#[test]
+fn test_code() {
+    let input = std::env::temp_dir().join(format!("pankat-test-code-{}", std::process::id()));
+    std::fs::create_dir_all(input.join("posts/libnix")).unwrap();
+    std::fs::write(
+        input.join("posts/libnix/main.rs"),
+        "use std::io;\n// ANCHOR: main\nfn main() {\n    println!(\"hi\");\n}\n// ANCHOR_END: main\n",
+    )
+    .unwrap();
+
+    let out = code(
+        &input,
+        "posts/libnix/libnix.mdwn",
+        "/blog",
+        "file=main.rs lines=3-5",
+    )
+    .unwrap();
+    assert_eq!(
+        out,
+        "\n<div class=\"pankat-code-caption\"><a href=\"/blog/posts/libnix/main.rs\">posts/libnix/main.rs</a>, lines 3-5</div>\n\n``` {.rust .numberLines startFrom=\"3\"}\nfn main() {\n    println!(\"hi\");\n}\n```\n"
+    );
+
+    let out = code(
+        &input,
+        "posts/libnix/libnix.mdwn",
+        "/blog",
+        "file=posts/libnix/main.rs region=main lang=c",
+    )
+    .unwrap();
+    assert!(out.contains(", region main</div>"));
+    assert!(out.contains("``` {.c .numberLines startFrom=\"3\"}\nfn main() {\n"));
+    assert!(!out.contains("ANCHOR"));
+
+    assert!(code(&input, "posts/a.mdwn", "", "file=main.rs").is_err());
+    assert!(code(
+        &input,
+        "posts/libnix/libnix.mdwn",
+        "",
+        "file=main.rs lines=1-2 region=main"
+    )
+    .is_err());
+    assert!(code(
+        &input,
+        "posts/libnix/libnix.mdwn",
+        "",
+        "file=main.rs region=foo"
+    )
+    .is_err());
+
+    std::fs::remove_dir_all(&input).unwrap();
+}
@@ -0,0 +1,123 @@
+use crate::articles::plugins::{
+    parse_key_value_arguments, wrap_block, Plugin, PluginArgument, PluginOutput,
+};
+use crate::articles::ArticleWithTags;
+use regex::Regex;
+use std::error::Error;
+
+/// `[[!columns]] [[!column]] ... [[!/column]] [[!column width=30%]] ... [[!/column]] [[!/columns]]`
+/// places the columns next to each other, on narrow screens they are stacked
+pub struct Columns;
+
+/// one column inside `[[!columns]]`
+pub struct Column;
+
+fn column(input: &str, body: &str) -> Result<String, Box<dyn Error>> {
+    let arguments = parse_key_value_arguments("column", input, &["width"])?;
+    let style = match arguments.get("width") {
+        Some(width) => {
+            let re = Regex::new(r"^\d+(\.\d+)?(%|px|em|rem)$").unwrap();
+            if !re.is_match(width) {
+                return Err(format!(
+                    "column: width must look like 30%, 200px or 10em, got '{}'",
+                    width
+                )
+                .into());
+            }
+            format!(r#" style="flex: 0 0 {}""#, width)
+        }
+        None => String::new(),
+    };
+    Ok(wrap_block(
+        &format!(r#"<div class="pankat-column"{}>"#, style),
+        body,
+        "</div>",
+    ))
+}
+
+impl Plugin for Columns {
+    fn name(&self) -> &str {
+        "columns"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Html
+    }
+
+    fn exec(&self, _input: &str, _article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        Err("columns: missing the closing [[!/columns]]".into())
+    }
+
+    fn is_block(&self) -> bool {
+        true
+    }
+
+    fn exec_block(
+        &self,
+        input: &str,
+        body: &str,
+        _article: &mut ArticleWithTags,
+    ) -> Result<String, Box<dyn Error>> {
+        parse_key_value_arguments("columns", input, &[])?;
+        if !body.contains(r#"<div class="pankat-column""#) {
+            return Err("columns: expected [[!column]] ... [[!/column]] blocks inside".into());
+        }
+        Ok(wrap_block(
+            r#"<div class="pankat-columns">"#,
+            body,
+            "</div>",
+        ))
+    }
+}
+
+impl Plugin for Column {
+    fn name(&self) -> &str {
+        "column"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[PluginArgument {
+            name: "width",
+            description: "fixed width of the column, e.g. width=30%, others share the rest",
+            required: false,
+        }]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Html
+    }
+
+    fn exec(&self, _input: &str, _article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        Err("column: missing the closing [[!/column]]".into())
+    }
+
+    fn is_block(&self) -> bool {
+        true
+    }
+
+    fn exec_block(
+        &self,
+        input: &str,
+        body: &str,
+        _article: &mut ArticleWithTags,
+    ) -> Result<String, Box<dyn Error>> {
+        column(input, body)
+    }
+}
+
+#[test]
+fn test_column() {
+    assert_eq!(
+        column("width=30%", "left").unwrap(),
+        "\n<div class=\"pankat-column\" style=\"flex: 0 0 30%\">\n\nleft\n\n</div>\n"
+    );
+    assert_eq!(
+        column("", "right").unwrap(),
+        "\n<div class=\"pankat-column\">\n\nright\n\n</div>\n"
+    );
+    assert!(column("width=wide", "x").is_err());
+}
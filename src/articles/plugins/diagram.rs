@@ -0,0 +1,130 @@
+use crate::articles::diagrams;
+use crate::articles::plugins::{parse_key_value_arguments, Plugin, PluginArgument, PluginOutput};
+use crate::articles::utils;
+use crate::articles::ArticleWithTags;
+use crate::config;
+use crate::renderer::diagram::{check_diagram_tool, DiagramTool};
+use std::error::Error;
+
+/// `[[!graphviz alt="build steps"]] digraph { a -> b } [[!/graphviz]]`, likewise `[[!plantuml]]`
+/// and `[[!mermaid]]`, renders the body with the local tool into an svg file
+///
+/// the svg is cached by the hash of the body in the cache table, the tool only runs when the
+/// diagram changed.
+pub struct Diagram(pub DiagramTool);
+
+fn diagram_html(
+    tool: DiagramTool,
+    prefix: &str,
+    hash: &str,
+    alt: &str,
+    caption: Option<&str>,
+) -> String {
+    format!(
+        r#"<figure class="pankat-diagram pankat-diagram-{}"><img src="{}/{}/{}" alt="{}">{}</figure>"#,
+        tool.name(),
+        prefix,
+        diagrams::DIAGRAMS_DIR,
+        diagrams::file_name(hash),
+        utils::escape_html(alt),
+        caption
+            .map(|c| format!("<figcaption>{}</figcaption>", utils::escape_html(c)))
+            .unwrap_or_default()
+    )
+}
+
+impl Plugin for Diagram {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[
+            PluginArgument {
+                name: "alt",
+                description: "alternative text of the diagram",
+                required: false,
+            },
+            PluginArgument {
+                name: "caption",
+                description: "caption shown below the diagram",
+                required: false,
+            },
+        ]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Html
+    }
+
+    fn exec(&self, _input: &str, _article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        Err(format!(
+            "{}: missing the closing [[!/{}]]",
+            self.0.name(),
+            self.0.name()
+        )
+        .into())
+    }
+
+    fn is_block(&self) -> bool {
+        true
+    }
+
+    fn exec_block(
+        &self,
+        input: &str,
+        body: &str,
+        _article: &mut ArticleWithTags,
+    ) -> Result<String, Box<dyn Error>> {
+        let name = self.0.name();
+        let arguments = parse_key_value_arguments(name, input, &["alt", "caption"])?;
+        let source = body.trim_matches('\n');
+        if source.trim().is_empty() {
+            return Err(format!("{}: the diagram is empty", name).into());
+        }
+        check_diagram_tool(self.0).map_err(|e| format!("{}: {}", name, e))?;
+        let prefix = config::Config::try_get()
+            .ok_or_else(|| format!("{}: config is not initialized", name))?
+            .subdir
+            .display()
+            .to_string();
+        let hash = diagrams::queue(self.0, source);
+        Ok(diagram_html(
+            self.0,
+            &prefix,
+            &hash,
+            arguments.get("alt").map_or(name, |alt| alt.as_str()),
+            arguments.get("caption").map(|caption| caption.as_str()),
+        ))
+    }
+}
+
+#[test]
+fn test_diagram_html() {
+    assert_eq!(
+        diagram_html(
+            DiagramTool::Mermaid,
+            "/blog",
+            "abc",
+            "a \"flow\"",
+            Some("Flow")
+        ),
+        r#"<figure class="pankat-diagram pankat-diagram-mermaid"><img src="/blog/diagrams/abc.svg" alt="a &quot;flow&quot;"><figcaption>Flow</figcaption></figure>"#
+    );
+
+    let mut article = ArticleWithTags {
+        src_file_name: "posts/diagram.mdwn".to_string(),
+        dst_file_name: String::new(),
+        ..Default::default()
+    };
+    let graphviz = Diagram(DiagramTool::Graphviz);
+    assert!(graphviz.exec("", &mut article).is_err());
+    assert!(graphviz
+        .exec_block("", "\n\n", &mut article)
+        .unwrap_err()
+        .to_string()
+        .contains("the diagram is empty"));
+    assert!(graphviz
+        .exec_block("size=10", "digraph { a -> b }", &mut article)
+        .is_err());
+}
@@ -1,7 +1,24 @@
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
 use crate::articles::ArticleWithTags;
 use std::error::Error;
 
-pub fn draft(_input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
-    article.draft = Some(true);
-    Ok("".to_string())
+pub struct Draft;
+
+impl Plugin for Draft {
+    fn name(&self) -> &str {
+        "draft"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, _input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        article.draft = Some(true);
+        Ok("".to_string())
+    }
 }
@@ -0,0 +1,180 @@
+use crate::articles::images;
+use crate::articles::plugins::{parse_key_value_arguments, Plugin, PluginArgument, PluginOutput};
+use crate::articles::utils;
+use crate::articles::ArticleWithTags;
+use crate::config;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// `[[!gallery dir=media/cats sort=date]]` renders the images of a directory as thumbnails
+/// which open in colorbox, a caption is read from the optional `<image>.txt` sidecar file
+pub struct Gallery;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Sort {
+    Name,
+    Date,
+}
+
+fn parse_arguments(input: &str) -> Result<(String, Sort), Box<dyn Error>> {
+    let arguments = parse_key_value_arguments("gallery", input, &["dir", "sort"])?;
+    let sort = match arguments.get("sort").map(|s| s.as_str()) {
+        None | Some("name") => Sort::Name,
+        Some("date") => Sort::Date,
+        Some(sort) => {
+            return Err(format!("gallery: sort must be 'name' or 'date', got '{}'", sort).into())
+        }
+    };
+    match arguments.get("dir") {
+        Some(dir) => Ok((dir.clone(), sort)),
+        None => Err("gallery: argument 'dir' is missing".into()),
+    }
+}
+
+fn find_dir(input: &Path, article: &ArticleWithTags, dir: &str) -> Option<PathBuf> {
+    utils::find_input_path(input, &article.src_file_name, dir).filter(|path| path.is_dir())
+}
+
+/// the images of `dir` which can be scaled, sorted by name or modification time
+fn gallery_images(dir: &Path, sort: Sort) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut images: Vec<(std::time::SystemTime, PathBuf)> = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let supported = path.extension().is_some_and(|ext| {
+            matches!(
+                ext.to_string_lossy().to_lowercase().as_str(),
+                "jpg" | "jpeg" | "png" | "webp"
+            )
+        });
+        if path.is_file() && supported {
+            let modified = std::fs::metadata(&path)?.modified()?;
+            images.push((modified, path));
+        }
+    }
+    match sort {
+        Sort::Name => images.sort_by(|a, b| a.1.cmp(&b.1)),
+        Sort::Date => images.sort(),
+    }
+    Ok(images.into_iter().map(|(_, path)| path).collect())
+}
+
+fn caption(image: &Path) -> Option<String> {
+    let mut sidecar = image.as_os_str().to_owned();
+    sidecar.push(".txt");
+    std::fs::read_to_string(PathBuf::from(sidecar))
+        .ok()
+        .map(|caption| caption.trim().to_string())
+        .filter(|caption| !caption.is_empty())
+}
+
+impl Plugin for Gallery {
+    fn name(&self) -> &str {
+        "gallery"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[
+            PluginArgument {
+                name: "dir",
+                description: "the directory with the images, e.g. dir=media/cats",
+                required: true,
+            },
+            PluginArgument {
+                name: "sort",
+                description: "order of the images, sort=name (default) or sort=date",
+                required: false,
+            },
+        ]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Html
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        let (dir, sort) = parse_arguments(input)?;
+        let cfg = config::Config::try_get().ok_or("gallery: config is not initialized")?;
+        let path = find_dir(&cfg.input, article, &dir)
+            .ok_or_else(|| format!("gallery: directory '{}' not found", dir))?;
+        let prefix = cfg.subdir.display().to_string();
+        let rel = format!("gallery-{}", dir.replace(['/', ' ', '.'], "-"));
+
+        let mut out = String::from(r#"<div class="pankat-gallery">"#);
+        for image in gallery_images(&path, sort)? {
+            let responsive_image = images::process(&image, &cfg.output)?;
+            let (thumbnail, thumbnail_width) = &responsive_image.variants[0];
+            let thumbnail_height = (responsive_image.height as u64 * *thumbnail_width as u64
+                / responsive_image.width.max(1) as u64) as u32;
+            let caption = caption(&image).map(|c| utils::escape_html(&c));
+            out += &format!(
+                r#"<figure><a href="{prefix}/{dir}/{}" rel="{rel}" title="{}"><img src="{prefix}/{dir}/{}" width="{}" height="{}" loading="lazy" alt="{}"></a>{}</figure>"#,
+                responsive_image.largest(),
+                caption.clone().unwrap_or_default(),
+                thumbnail,
+                thumbnail_width,
+                thumbnail_height,
+                caption.clone().unwrap_or_default(),
+                caption
+                    .map(|c| format!("<figcaption>{}</figcaption>", c))
+                    .unwrap_or_default(),
+                dir = images::IMAGES_DIR,
+            );
+        }
+        out += "</div>";
+        Ok(out)
+    }
+
+    fn dependencies(&self, input: &str, article: &ArticleWithTags) -> Vec<PathBuf> {
+        let (dir, _) = match parse_arguments(input) {
+            Ok(arguments) => arguments,
+            Err(_) => return Vec::new(),
+        };
+        let cfg = match config::Config::try_get() {
+            Some(cfg) => cfg,
+            None => return Vec::new(),
+        };
+        match find_dir(&cfg.input, article, &dir) {
+            Some(path) => vec![utils::relative_to_input(&cfg.input, &path)],
+            None => Vec::new(),
+        }
+    }
+}
+
+#[test]
+fn test_parse_arguments() {
+    assert_eq!(
+        parse_arguments("dir=media/cats").unwrap(),
+        ("media/cats".to_string(), Sort::Name)
+    );
+    assert_eq!(
+        parse_arguments(r#"dir="cats" sort=date"#).unwrap(),
+        ("cats".to_string(), Sort::Date)
+    );
+    assert!(parse_arguments("sort=date").is_err());
+    assert!(parse_arguments("dir=cats sort=size").is_err());
+    assert!(parse_arguments("dir=cats foo=bar").is_err());
+    assert!(parse_arguments("cats").is_err());
+    assert_eq!(
+        parse_arguments(r#"dir="my cats""#).unwrap(),
+        ("my cats".to_string(), Sort::Name)
+    );
+}
+
+#[test]
+fn test_gallery_images() {
+    let dir = std::env::temp_dir().join(format!("pankat-test-gallery-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for name in ["b.png", "a.jpg", "c.svg", "a.jpg.txt"] {
+        std::fs::write(dir.join(name), b"").unwrap();
+    }
+    std::fs::write(dir.join("a.jpg.txt"), "a cat\n").unwrap();
+
+    assert_eq!(
+        gallery_images(&dir, Sort::Name).unwrap(),
+        vec![dir.join("a.jpg"), dir.join("b.png")]
+    );
+    assert_eq!(caption(&dir.join("a.jpg")), Some("a cat".to_string()));
+    assert_eq!(caption(&dir.join("b.png")), None);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,14 +1,144 @@
+use crate::articles::images;
+use crate::articles::plugins::arguments::parse_arguments;
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
+use crate::articles::utils;
 use crate::articles::ArticleWithTags;
+use crate::config;
 use std::error::Error;
+use std::path::PathBuf;
 
-pub fn img(input: &str, _article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
-    let mut parts = input.split_whitespace();
-    let img_url = parts.next().unwrap_or("").to_string();
-    let attributes = parts.collect::<Vec<&str>>().join(" ");
+/// `[[!img foo.jpg alt="a cat on a mat" size=300x]]`, all arguments except `size` become
+/// attributes of the img element
+pub struct Img;
 
-    let out = format!(
-        r#"<a href="{}"><img src="{}" {}></a>"#,
-        img_url, img_url, attributes
+/// the url and the html attributes of the img element
+fn parse_img_arguments(input: &str) -> Result<(String, String), Box<dyn Error>> {
+    let arguments = parse_arguments(input).map_err(|e| format!("img: {}", e))?;
+    arguments
+        .expect_positional(1)
+        .map_err(|e| format!("img: {}", e))?;
+    let img_url = match arguments.positional().first() {
+        Some(img_url) => img_url.to_string(),
+        None => return Err("img: argument 'url' is missing".into()),
+    };
+
+    let mut attributes: Vec<String> = Vec::new();
+    for (key, value) in arguments.named() {
+        match key {
+            // ikiwiki style WIDTHxHEIGHT, either side may be left out
+            "size" => {
+                let (width, height) = value.split_once('x').ok_or_else(|| {
+                    format!(
+                        "img: size must look like 300x200, 300x or x200, got '{}'",
+                        value
+                    )
+                })?;
+                for (name, size) in [("width", width), ("height", height)] {
+                    if size.is_empty() {
+                        continue;
+                    }
+                    if size.parse::<u32>().is_err() {
+                        return Err(format!("img: size must be in pixels, got '{}'", value).into());
+                    }
+                    if arguments.get(name).is_some() {
+                        return Err(format!("img: use either 'size' or '{}'", name).into());
+                    }
+                    attributes.push(format!(r#"{}="{}""#, name, size));
+                }
+            }
+            _ => attributes.push(format!(r#"{}="{}""#, key, utils::escape_html(value))),
+        }
+    }
+    Ok((img_url, attributes.join(" ")))
+}
+
+impl Plugin for Img {
+    fn name(&self) -> &str {
+        "img"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[
+            PluginArgument {
+                name: "url",
+                description: "the image location, used for the link and the image",
+                required: true,
+            },
+            PluginArgument {
+                name: "attributes",
+                description: "html attributes added to the img element",
+                required: false,
+            },
+        ]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Html
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        let (img_url, attributes) = parse_img_arguments(input)?;
+
+        // images found in the input are published as downscaled variants, others are linked
+        if let Some(cfg) = config::Config::try_get() {
+            if let Some(source) = images::find_source(&cfg.input, &article.src_file_name, &img_url)
+            {
+                let responsive_image = images::process(&source, &cfg.output)?;
+                return Ok(responsive_image.to_html(&cfg.subdir.display().to_string(), &attributes));
+            }
+        }
+
+        let out = format!(
+            r#"<a href="{}"><img src="{}" {}></a>"#,
+            img_url, img_url, attributes
+        );
+        Ok(out.to_string())
+    }
+
+    fn dependencies(&self, input: &str, article: &ArticleWithTags) -> Vec<PathBuf> {
+        let img_url = match parse_img_arguments(input) {
+            Ok((img_url, _)) => img_url,
+            Err(_) => return Vec::new(),
+        };
+        match config::Config::try_get() {
+            Some(cfg) => images::find_source(&cfg.input, &article.src_file_name, &img_url)
+                .map(|path| utils::relative_to_input(&cfg.input, &path))
+                .into_iter()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[test]
+fn test_parse_img_arguments() {
+    assert_eq!(
+        parse_img_arguments(r#"foo.jpg alt="a cat on a mat" size=300x"#).unwrap(),
+        (
+            "foo.jpg".to_string(),
+            r#"alt="a cat on a mat" width="300""#.to_string()
+        )
+    );
+    assert_eq!(
+        parse_img_arguments(r#"media/pepper.png width="400px" alt="" style="float: right""#)
+            .unwrap()
+            .1,
+        r#"width="400px" alt="" style="float: right""#
+    );
+    assert_eq!(
+        parse_img_arguments(r#"a.png alt="a cat"on a mat"#)
+            .unwrap_err()
+            .to_string(),
+        "img: expected whitespace after the quoted value at column 18"
+    );
+    assert_eq!(
+        parse_img_arguments(r#"a.png title="<b>" size=x20"#)
+            .unwrap()
+            .1,
+        r#"title="&lt;b&gt;" height="20""#
     );
-    Ok(out.to_string())
+    assert!(parse_img_arguments("a.png b.png").is_err());
+    assert!(parse_img_arguments("alt=foo").is_err());
+    assert!(parse_img_arguments("a.png size=big").is_err());
+    assert!(parse_img_arguments("a.png size=300x width=200").is_err());
 }
@@ -0,0 +1,208 @@
+use crate::articles::plugins::arguments::parse_arguments;
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
+use crate::articles::utils;
+use crate::articles::ArticleWithTags;
+use crate::config;
+use scopeguard::defer;
+use std::cell::RefCell;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// `[[!include snippets/disclaimer.md]]` splices another markdown file into the article, the
+/// directives in the included file are evaluated as well
+///
+/// paths are looked up in the input, `input/media` and the directory of the including file.
+pub struct Include;
+
+thread_local! {
+    /// files currently being included, relative to the input, used to detect cycles
+    static INCLUDE_STACK: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    /// the input the outermost include resolved its path against
+    static INCLUDE_INPUT: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// the path of `[[!include path]]`, quote paths containing whitespace
+fn path_argument(input: &str) -> Result<String, Box<dyn Error>> {
+    let arguments = parse_arguments(input).map_err(|e| format!("include: {}", e))?;
+    arguments
+        .expect_positional(1)
+        .and_then(|_| arguments.expect_keys(&[]))
+        .map_err(|e| format!("include: {}", e))?;
+    match arguments.positional().first() {
+        Some(path) => Ok(path.to_string()),
+        None => Err("include: argument 'path' is missing".into()),
+    }
+}
+
+fn find_file(input: &Path, including_file: &str, path: &str) -> Result<PathBuf, Box<dyn Error>> {
+    utils::find_input_path(input, including_file, path)
+        .filter(|p| p.is_file())
+        .ok_or_else(|| format!("include: file '{}' not found", path).into())
+}
+
+fn relative(input: &Path, path: &Path) -> String {
+    utils::relative_to_input(input, path).display().to_string()
+}
+
+fn include(
+    input: &Path,
+    path: &str,
+    article: &mut ArticleWithTags,
+) -> Result<String, Box<dyn Error>> {
+    let mut chain: Vec<String> = vec![article.src_file_name.clone()];
+    INCLUDE_STACK.with(|stack| chain.extend(stack.borrow().iter().cloned()));
+
+    let file = find_file(input, chain.last().unwrap(), path)?;
+    let file_name = relative(input, &file);
+    if chain.contains(&file_name) {
+        chain.push(file_name);
+        return Err(format!("include: cycle detected: {}", chain.join(" -> ")).into());
+    }
+    let content = std::fs::read_to_string(&file)
+        .map_err(|e| format!("include: can't read '{}': {}", file.display(), e))?;
+
+    INCLUDE_STACK.with(|stack| stack.borrow_mut().push(file_name));
+    let outermost = INCLUDE_INPUT.with(|i| i.replace(Some(input.to_path_buf())).is_none());
+    defer! {
+        INCLUDE_STACK.with(|stack| stack.borrow_mut().pop());
+        if outermost {
+            INCLUDE_INPUT.with(|i| i.replace(None));
+        }
+    }
+    crate::articles::eval_plugins(&content, article)
+}
+
+/// the included file and all files it includes, relative to the input
+fn dependencies(input: &Path, including_file: &str, path: &str, found: &mut Vec<PathBuf>) {
+    let file = match find_file(input, including_file, path) {
+        Ok(file) => file,
+        Err(_) => return,
+    };
+    let file_name = PathBuf::from(relative(input, &file));
+    if found.contains(&file_name) {
+        return;
+    }
+    found.push(file_name.clone());
+    if let Ok(content) = std::fs::read_to_string(&file) {
+        for (start, end) in crate::articles::find_directives(&content) {
+            let path = match crate::articles::split_directive(&content[start..end]) {
+                Some(("include", argument)) => path_argument(argument),
+                _ => continue,
+            };
+            if let Ok(path) = path {
+                dependencies(input, &file_name.display().to_string(), &path, found);
+            }
+        }
+    }
+}
+
+impl Plugin for Include {
+    fn name(&self) -> &str {
+        "include"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[PluginArgument {
+            name: "path",
+            description: "the markdown file to include, e.g. snippets/disclaimer.md",
+            required: true,
+        }]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Markdown
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        let input_dir: PathBuf = match INCLUDE_INPUT.with(|i| i.borrow().clone()) {
+            Some(input_dir) => input_dir,
+            None => config::Config::try_get()
+                .ok_or("include: config is not initialized")?
+                .input
+                .clone(),
+        };
+        include(&input_dir, &path_argument(input)?, article)
+    }
+
+    fn dependencies(&self, input: &str, article: &ArticleWithTags) -> Vec<PathBuf> {
+        let mut found: Vec<PathBuf> = Vec::new();
+        if let (Some(cfg), Ok(path)) = (config::Config::try_get(), path_argument(input)) {
+            dependencies(&cfg.input, &article.src_file_name, &path, &mut found);
+        }
+        found
+    }
+}
+
+#[test]
+fn test_include() {
+    let input = std::env::temp_dir().join(format!("pankat-test-include-{}", std::process::id()));
+    std::fs::create_dir_all(input.join("posts/snippets")).unwrap();
+    std::fs::write(
+        input.join("posts/snippets/install.md"),
+        "run `nix-shell`\n[[!include footer.md]]\n[[!tag nix]]",
+    )
+    .unwrap();
+    std::fs::write(input.join("posts/snippets/footer.md"), "the end").unwrap();
+    std::fs::write(input.join("posts/snippets/a.md"), "[[!include b.md]]").unwrap();
+    std::fs::write(input.join("posts/snippets/b.md"), "[[!include a.md]]").unwrap();
+
+    let mut article = ArticleWithTags {
+        src_file_name: "posts/article.mdwn".to_string(),
+        dst_file_name: String::new(),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        include(&input, "snippets/install.md", &mut article).unwrap(),
+        "run `nix-shell`\nthe end\n"
+    );
+    assert_eq!(article.tags, Some(vec!["nix".to_string()]));
+    assert!(include(&input, "snippets/missing.md", &mut article).is_err());
+
+    // a -> b -> a, the inner include is reported and left as it is
+    assert_eq!(
+        include(&input, "snippets/a.md", &mut article).unwrap(),
+        "[[!include a.md]]"
+    );
+    article.src_file_name = "posts/snippets/a.md".to_string();
+    let error = include(&input, "a.md", &mut article)
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        error,
+        "include: cycle detected: posts/snippets/a.md -> posts/snippets/a.md"
+    );
+
+    let mut found: Vec<PathBuf> = Vec::new();
+    dependencies(
+        &input,
+        "posts/article.mdwn",
+        "snippets/install.md",
+        &mut found,
+    );
+    assert_eq!(
+        found,
+        vec![
+            PathBuf::from("posts/snippets/install.md"),
+            PathBuf::from("posts/snippets/footer.md")
+        ]
+    );
+
+    // a path leading up is stored normalized, like the file monitor reports it
+    std::fs::create_dir_all(input.join("posts/notes")).unwrap();
+    let mut found: Vec<PathBuf> = Vec::new();
+    dependencies(
+        &input,
+        "posts/notes/article.mdwn",
+        "../snippets/footer.md",
+        &mut found,
+    );
+    assert_eq!(found, vec![PathBuf::from("posts/snippets/footer.md")]);
+    article.src_file_name = "posts/notes/article.mdwn".to_string();
+    assert_eq!(
+        include(&input, "../snippets/footer.md", &mut article).unwrap(),
+        "the end"
+    );
+
+    std::fs::remove_dir_all(&input).unwrap();
+}
@@ -0,0 +1,46 @@
+use crate::articles::inline::{inline_placeholder, InlineQuery};
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
+use crate::articles::ArticleWithTags;
+use std::error::Error;
+
+/// `[[!inline tags=nix series=libnix limit=10]]` lists the matching articles with title, date
+/// and summary, newest first, without arguments all visible articles are listed
+///
+/// the list is filled in when the article is written, pages using it are rewritten when a
+/// matching article is added, removed or retagged.
+pub struct Inline;
+
+impl Plugin for Inline {
+    fn name(&self) -> &str {
+        "inline"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[
+            PluginArgument {
+                name: "tags",
+                description: "comma separated tags, articles with one of them are listed",
+                required: false,
+            },
+            PluginArgument {
+                name: "series",
+                description: "only articles of this series are listed",
+                required: false,
+            },
+            PluginArgument {
+                name: "limit",
+                description: "the maximum number of listed articles",
+                required: false,
+            },
+        ]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Html
+    }
+
+    fn exec(&self, input: &str, _article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        let query = InlineQuery::parse(input)?;
+        Ok(format!("\n\n{}\n\n", inline_placeholder(&query)))
+    }
+}
@@ -0,0 +1,32 @@
+use crate::articles::links::link_placeholder;
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
+use crate::articles::ArticleWithTags;
+use std::error::Error;
+
+pub struct Link;
+
+impl Plugin for Link {
+    fn name(&self) -> &str {
+        "link"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[PluginArgument {
+            name: "target",
+            description: "src_file_name, file name without .mdwn or title of the linked article",
+            required: true,
+        }]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Html
+    }
+
+    fn exec(&self, input: &str, _article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        if input.contains('\n') || input.contains('\t') {
+            Err("Argument contains invalid characters (newlines or tabs)".into())
+        } else {
+            Ok(link_placeholder(input.trim()))
+        }
+    }
+}
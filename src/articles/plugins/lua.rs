@@ -0,0 +1,151 @@
+use crate::articles::plugins::arguments::parse_arguments;
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
+use crate::articles::ArticleWithTags;
+use chrono::NaiveDateTime;
+use mlua::{Lua, Table, Value};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// a user directive implemented in `<lua_plugins_dir>/<name>.lua`
+///
+/// the script defines a global function `render(args, article, params)` which gets the argument
+/// string, a table with `title`, `tags`, `series`, `summary` and `modification_date`
+/// (YYYY-MM-DD HH:MM) and the parsed arguments, positional ones at `params[1..n]` and `key=value`
+/// ones as `params.key`, and returns the replacement markdown. changes to the article table are
+/// written back.
+pub struct LuaPlugin {
+    name: String,
+    path: PathBuf,
+}
+
+impl LuaPlugin {
+    pub fn new(name: &str, path: PathBuf) -> Self {
+        LuaPlugin {
+            name: name.to_string(),
+            path,
+        }
+    }
+
+    /// looks up `<dir>/<name>.lua`
+    pub fn find(dir: &Path, name: &str) -> Option<LuaPlugin> {
+        let path = dir.join(format!("{}.lua", name.to_lowercase()));
+        if path.is_file() {
+            Some(LuaPlugin::new(&name.to_lowercase(), path))
+        } else {
+            None
+        }
+    }
+
+    /// names of all `*.lua` scripts in `dir`
+    pub fn names(dir: &Path) -> Vec<String> {
+        let mut names = Vec::new();
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "lua") {
+                    if let Some(stem) = path.file_stem() {
+                        names.push(stem.to_string_lossy().to_lowercase());
+                    }
+                }
+            }
+        }
+        names
+    }
+}
+
+impl Plugin for LuaPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Markdown
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        let script = std::fs::read_to_string(&self.path).map_err(|e| {
+            format!(
+                "Lua plugin '{}' can't read '{}': {}",
+                self.name,
+                self.path.display(),
+                e
+            )
+        })?;
+        run_script(&self.name, &script, input, article)
+    }
+}
+
+pub fn run_script(
+    name: &str,
+    script: &str,
+    input: &str,
+    article: &mut ArticleWithTags,
+) -> Result<String, Box<dyn Error>> {
+    let lua = Lua::new();
+    lua.load(script).set_name(name).exec()?;
+
+    let render: mlua::Function = match lua.globals().get("render")? {
+        Value::Function(f) => f,
+        _ => return Err(format!("Lua plugin '{}' does not define 'render'", name).into()),
+    };
+
+    let article_table: Table = lua.create_table()?;
+    article_table.set("src_file_name", article.src_file_name.clone())?;
+    article_table.set("title", article.title.clone())?;
+    article_table.set("summary", article.summary.clone())?;
+    article_table.set("series", article.series.clone())?;
+    article_table.set("tags", article.tags.clone())?;
+    article_table.set(
+        "modification_date",
+        article
+            .modification_date
+            .map(|d| d.format(DATE_FORMAT).to_string()),
+    )?;
+
+    // nil if the argument string doesn't parse, the script can still use the raw string
+    let params: Option<Table> = match parse_arguments(input) {
+        Ok(arguments) => {
+            let params: Table = lua.create_table()?;
+            let mut position = 1;
+            for argument in arguments.0 {
+                match argument.key {
+                    Some(key) => params.set(key, argument.value)?,
+                    None => {
+                        params.set(position, argument.value)?;
+                        position += 1;
+                    }
+                }
+            }
+            Some(params)
+        }
+        Err(_) => None,
+    };
+
+    let output: Option<String> = render.call((input, article_table.clone(), params))?;
+
+    article.title = article_table.get("title")?;
+    article.summary = article_table.get("summary")?;
+    article.series = article_table.get("series")?;
+    article.tags = article_table.get("tags")?;
+    article.modification_date = match article_table.get::<_, Option<String>>("modification_date")? {
+        Some(date) => match NaiveDateTime::parse_from_str(&date, DATE_FORMAT) {
+            Ok(d) => Some(d),
+            Err(e) => {
+                return Err(format!(
+                    "Lua plugin '{}' set invalid modification_date '{}': {}",
+                    name, date, e
+                )
+                .into())
+            }
+        },
+        None => None,
+    };
+
+    Ok(output.unwrap_or_default())
+}
@@ -1,18 +1,223 @@
+use crate::articles::front_matter::parse_date;
+use crate::articles::plugins::{parse_key_value_arguments, Plugin, PluginArgument, PluginOutput};
 use crate::articles::ArticleWithTags;
 use chrono::NaiveDateTime;
 use regex::Regex;
 use std::error::Error;
 
-pub fn meta(input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
-    let re = Regex::new(r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}").unwrap();
-    if let Some(mat) = re.find(&input) {
-        if let Ok(parsed_time) = NaiveDateTime::parse_from_str(mat.as_str(), "%Y-%m-%d %H:%M") {
-            article.modification_date = Some(parsed_time);
-            return Ok("".to_string());
-        } else {
-            Err("Argument contains invalid characters (newlines or tabs)".into())
+/// `[[!meta date="2024-04-12 20:53" updated="2025-01-10 09:00" author="Joachim" lang=en]]`
+///
+/// `date`, or its alias `created`, is the publication date the timeline is sorted by, `updated`
+/// is shown as "last updated" next to it. the old `[[!meta 2024-04-12 20:53]]` form is still
+/// supported.
+pub struct Meta;
+
+const KEYS: [&str; 8] = [
+    "date",
+    "created",
+    "updated",
+    "author",
+    "lang",
+    "canonical",
+    "description",
+    "keywords",
+];
+
+fn date(key: &str, value: &str) -> Result<NaiveDateTime, Box<dyn Error>> {
+    parse_date(value).ok_or_else(|| {
+        format!(
+            "meta: '{}' has unsupported format '{}', use YYYY-MM-DD HH:MM",
+            key, value
+        )
+        .into()
+    })
+}
+
+/// a field may be repeated by another [[!meta]] directive but not changed
+fn set<T: PartialEq + std::fmt::Debug>(
+    key: &str,
+    field: &mut Option<T>,
+    value: T,
+) -> Result<(), Box<dyn Error>> {
+    match field {
+        Some(previous) if *previous != value => Err(format!(
+            "meta: '{}' is already set to {:?} by another [[!meta]] directive",
+            key, previous
+        )
+        .into()),
+        _ => {
+            *field = Some(value);
+            Ok(())
         }
-    } else {
-        Err("Argument contains invalid characters (newlines or tabs)".into())
     }
 }
+
+fn meta(input: &str, article: &mut ArticleWithTags) -> Result<(), Box<dyn Error>> {
+    if !input.contains('=') {
+        let re = Regex::new(r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}").unwrap();
+        let timestamp = re
+            .find(input)
+            .ok_or("meta: expected key=value or a date in the format YYYY-MM-DD HH:MM")?;
+        return set(
+            "date",
+            &mut article.modification_date,
+            date("date", timestamp.as_str())?,
+        );
+    }
+
+    let arguments = parse_key_value_arguments("meta", input, &KEYS)?;
+    if arguments.contains_key("date") && arguments.contains_key("created") {
+        return Err("meta: 'created' is an alias for 'date', use only one of them".into());
+    }
+    let language_tag = Regex::new(r"^[A-Za-z]{2,3}(-[A-Za-z0-9]{1,8})*$").unwrap();
+    for (key, value) in arguments {
+        let value = value.trim().to_string();
+        match key.as_str() {
+            "date" | "created" => set("date", &mut article.modification_date, date(&key, &value)?)?,
+            "updated" => set("updated", &mut article.updated, date(&key, &value)?)?,
+            "author" => set("author", &mut article.author, value)?,
+            "lang" => {
+                if !language_tag.is_match(&value) {
+                    return Err(format!(
+                        "meta: lang must be a language tag like 'en' or 'de-CH', got '{}'",
+                        value
+                    )
+                    .into());
+                }
+                set("lang", &mut article.lang, value)?
+            }
+            "canonical" => {
+                if !value.starts_with("https://") && !value.starts_with("http://") {
+                    return Err(format!(
+                        "meta: canonical must be an absolute http(s) url, got '{}'",
+                        value
+                    )
+                    .into());
+                }
+                set("canonical", &mut article.canonical, value)?
+            }
+            "description" => set("description", &mut article.description, value)?,
+            "keywords" => {
+                let keywords: Vec<&str> = value
+                    .split(',')
+                    .map(|keyword| keyword.trim())
+                    .filter(|keyword| !keyword.is_empty())
+                    .collect();
+                set("keywords", &mut article.keywords, keywords.join(", "))?
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    if let (Some(created), Some(updated)) = (article.modification_date, article.updated) {
+        if updated < created {
+            return Err(format!(
+                "meta: updated {} is before the publication date {}",
+                updated, created
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+impl Plugin for Meta {
+    fn name(&self) -> &str {
+        "meta"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[
+            PluginArgument {
+                name: "date",
+                description: "publication date in the format YYYY-MM-DD HH:MM, alias created",
+                required: false,
+            },
+            PluginArgument {
+                name: "updated",
+                description: "date of the last update in the format YYYY-MM-DD HH:MM",
+                required: false,
+            },
+            PluginArgument {
+                name: "author",
+                description: "the author of the article",
+                required: false,
+            },
+            PluginArgument {
+                name: "lang",
+                description: "language of the article, e.g. lang=en",
+                required: false,
+            },
+            PluginArgument {
+                name: "canonical",
+                description: "canonical url of the article, e.g. canonical=https://example.com/a",
+                required: false,
+            },
+            PluginArgument {
+                name: "description",
+                description: "the html meta description",
+                required: false,
+            },
+            PluginArgument {
+                name: "keywords",
+                description: "comma separated keywords, e.g. keywords=\"nix, rust\"",
+                required: false,
+            },
+        ]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        meta(input, article)?;
+        Ok("".to_string())
+    }
+}
+
+#[test]
+fn test_meta_key_value() {
+    let mut article = ArticleWithTags {
+        src_file_name: "example.mdwn".to_string(),
+        dst_file_name: String::new(),
+        ..Default::default()
+    };
+
+    meta(
+        r#"created="2024-04-12 20:53" updated="2025-01-10" author="Joachim Schiele" lang=de-CH keywords="nix,  rust,""#,
+        &mut article,
+    )
+    .unwrap();
+    assert_eq!(
+        article.modification_date,
+        NaiveDateTime::parse_from_str("2024-04-12 20:53", "%Y-%m-%d %H:%M").ok()
+    );
+    assert_eq!(
+        article.updated,
+        NaiveDateTime::parse_from_str("2025-01-10 00:00", "%Y-%m-%d %H:%M").ok()
+    );
+    assert_eq!(article.author, Some("Joachim Schiele".to_string()));
+    assert_eq!(article.lang, Some("de-CH".to_string()));
+    assert_eq!(article.keywords, Some("nix, rust".to_string()));
+
+    // repeating a value is fine, changing it is not
+    meta(r#"date="2024-04-12 20:53""#, &mut article).unwrap();
+    assert!(meta("author=someone", &mut article).is_err());
+
+    let mut fresh = article.clone();
+    fresh.modification_date = None;
+    fresh.updated = None;
+    meta("2021-04-17 20:53", &mut fresh).unwrap();
+    assert!(meta(r#"updated="2020-01-01 10:00""#, &mut fresh).is_err());
+
+    assert!(meta("lang=english!", &mut fresh).is_err());
+    assert!(meta("canonical=/about.html", &mut fresh).is_err());
+    assert!(meta("date=yesterday", &mut fresh).is_err());
+    assert!(meta("foo=bar", &mut fresh).is_err());
+    assert!(meta(
+        r#"date="2024-04-12 20:53" created="2024-04-12 20:53""#,
+        &mut fresh
+    )
+    .is_err());
+}
@@ -1,8 +1,277 @@
+use crate::articles::ArticleWithTags;
+use crate::renderer::diagram::DiagramTool;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+pub mod arguments;
+pub mod bibliography;
+pub mod code;
+pub mod columns;
+pub mod diagram;
 pub mod draft;
+pub mod gallery;
 pub mod img;
+pub mod include;
+pub mod inline;
+pub mod link;
+pub mod lua;
 pub mod meta;
+pub mod note;
+pub mod options;
+pub mod schedule;
 pub mod series;
 pub mod specialpage;
 pub mod summary;
 pub mod tag;
 pub mod title;
+pub mod toggle;
+
+/// what a plugin does with its `[[!name ...]]` directive
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PluginOutput {
+    /// changes the `ArticleWithTags` metadata and removes the directive from the document
+    Metadata,
+    /// replaces the directive with markdown which is later processed by pandoc
+    Markdown,
+    /// replaces the directive with raw html which pandoc passes through
+    Html,
+}
+
+/// describes one argument a plugin accepts
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PluginArgument {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub required: bool,
+}
+
+pub trait Plugin: Send + Sync {
+    /// the directive name, `title` is used as `[[!title ...]]`
+    fn name(&self) -> &str;
+    /// the argument schema, used for validation and error messages
+    fn arguments(&self) -> &'static [PluginArgument];
+    fn output(&self) -> PluginOutput;
+    /// called with the trimmed argument string of the directive
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>>;
+    /// files or directories, relative to the input, the output depends on; the article is
+    /// rebuilt when one of them changes
+    fn dependencies(&self, _input: &str, _article: &ArticleWithTags) -> Vec<PathBuf> {
+        Vec::new()
+    }
+    /// block directives are closed by `[[!/name]]` and get the markdown in between
+    fn is_block(&self) -> bool {
+        false
+    }
+    /// called with the body of a block directive, its directives are already evaluated
+    fn exec_block(
+        &self,
+        _input: &str,
+        _body: &str,
+        _article: &mut ArticleWithTags,
+    ) -> Result<String, Box<dyn Error>> {
+        Err(format!("Plugin '{}' is not a block directive", self.name()).into())
+    }
+}
+
+/// wraps the markdown `body` in raw html, the blank lines make pandoc render the body as markdown
+pub fn wrap_block(open: &str, body: &str, close: &str) -> String {
+    format!("\n{}\n\n{}\n\n{}\n", open, body.trim_matches('\n'), close)
+}
+
+/// parses `key=value key="quoted value"` arguments, only the given keys are accepted
+pub fn parse_key_value_arguments(
+    name: &str,
+    input: &str,
+    keys: &[&str],
+) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+    let arguments = arguments::parse_arguments(input).map_err(|e| format!("{}: {}", name, e))?;
+    arguments
+        .expect_positional(0)
+        .and_then(|_| arguments.expect_keys(keys))
+        .map_err(|e| format!("{}: {}", name, e))?;
+    Ok(arguments.into_map())
+}
+
+fn check_required_arguments(plugin: &dyn Plugin, input: &str) -> Result<(), Box<dyn Error>> {
+    if input.is_empty() {
+        if let Some(argument) = plugin.arguments().iter().find(|a| a.required) {
+            return Err(format!(
+                "Plugin '{}' requires argument '{}' ({})",
+                plugin.name(),
+                argument.name,
+                argument.description
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+pub struct PluginRegistry {
+    plugins: BTreeMap<String, Box<dyn Plugin>>,
+    /// directives which are not registered are looked up as `<name>.lua` in this directory
+    lua_plugins_dir: Option<PathBuf>,
+}
+
+impl Default for PluginRegistry {
+    fn default() -> Self {
+        PluginRegistry::new()
+    }
+}
+
+static SINGLETON: OnceLock<Arc<PluginRegistry>> = OnceLock::new();
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        PluginRegistry {
+            plugins: BTreeMap::new(),
+            lua_plugins_dir: None,
+        }
+    }
+
+    /// a registry containing all plugins shipped with pankat
+    pub fn with_builtin_plugins() -> Self {
+        let mut registry = PluginRegistry::new();
+        let builtin: Vec<Box<dyn Plugin>> = vec![
+            Box::new(bibliography::Bibliography),
+            Box::new(code::Code),
+            Box::new(columns::Column),
+            Box::new(columns::Columns),
+            Box::new(draft::Draft),
+            Box::new(schedule::ExpireAt),
+            Box::new(gallery::Gallery),
+            Box::new(diagram::Diagram(DiagramTool::Graphviz)),
+            Box::new(img::Img),
+            Box::new(include::Include),
+            Box::new(inline::Inline),
+            Box::new(link::Link),
+            Box::new(diagram::Diagram(DiagramTool::Mermaid)),
+            Box::new(meta::Meta),
+            Box::new(note::Note),
+            Box::new(options::Options),
+            Box::new(diagram::Diagram(DiagramTool::PlantUml)),
+            Box::new(schedule::PublishAt),
+            Box::new(series::Series),
+            Box::new(specialpage::SpecialPage),
+            Box::new(summary::Summary),
+            Box::new(tag::Tag),
+            Box::new(title::Title),
+            Box::new(toggle::Toggle),
+        ];
+        for plugin in builtin {
+            registry
+                .register(plugin)
+                .expect("Builtin plugin names must be unique");
+        }
+        registry
+    }
+
+    /// returns the registry set by `initialize` or, if none was set, the builtin plugins
+    pub fn get() -> &'static Arc<PluginRegistry> {
+        SINGLETON.get_or_init(|| Arc::new(PluginRegistry::with_builtin_plugins()))
+    }
+
+    pub fn initialize(registry: PluginRegistry) -> Result<(), Box<dyn Error + Send + Sync>> {
+        SINGLETON
+            .set(Arc::new(registry))
+            .map_err(|_| "PluginRegistry can only be initialized once".into())
+    }
+
+    pub fn register(&mut self, plugin: Box<dyn Plugin>) -> Result<(), Box<dyn Error>> {
+        let name = plugin.name().to_lowercase();
+        if self.plugins.contains_key(&name) {
+            return Err(format!("Plugin '{}' is already registered", name).into());
+        }
+        self.plugins.insert(name, plugin);
+        Ok(())
+    }
+
+    pub fn set_lua_plugins_dir(&mut self, dir: PathBuf) {
+        self.lua_plugins_dir = Some(dir);
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<&dyn Plugin> {
+        self.plugins.get(&name.to_lowercase()).map(|p| p.as_ref())
+    }
+
+    /// names of the registered plugins followed by the lua plugins not shadowed by them
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.plugins.keys().cloned().collect();
+        if let Some(dir) = &self.lua_plugins_dir {
+            let mut lua_names: Vec<String> = lua::LuaPlugin::names(dir)
+                .into_iter()
+                .filter(|name| !self.plugins.contains_key(name))
+                .collect();
+            lua_names.sort();
+            names.append(&mut lua_names);
+        }
+        names
+    }
+
+    pub fn exec(
+        &self,
+        name: &str,
+        input: &str,
+        article: &mut ArticleWithTags,
+    ) -> Result<String, Box<dyn Error>> {
+        let lua_plugin: Option<lua::LuaPlugin> = match &self.lua_plugins_dir {
+            Some(dir) if self.lookup(name).is_none() => lua::LuaPlugin::find(dir, name),
+            _ => None,
+        };
+        let plugin: Option<&dyn Plugin> = match &lua_plugin {
+            Some(lua_plugin) => Some(lua_plugin),
+            None => self.lookup(name),
+        };
+        match plugin {
+            Some(plugin) => {
+                check_required_arguments(plugin, input)?;
+                let output = plugin.exec(input, article)?;
+                if plugin.output() == PluginOutput::Metadata && !output.is_empty() {
+                    return Err(format!(
+                        "Plugin '{}' only changes metadata but returned content",
+                        plugin.name()
+                    )
+                    .into());
+                }
+                Ok(output)
+            }
+            None => Err(format!(
+                "Plugin '{}' is not supported, available plugins: {}",
+                name,
+                self.names().join(", ")
+            )
+            .into()),
+        }
+    }
+
+    /// true for plugins closed by `[[!/name]]`, lua plugins are never blocks
+    pub fn is_block(&self, name: &str) -> bool {
+        self.lookup(name).is_some_and(|plugin| plugin.is_block())
+    }
+
+    pub fn exec_block(
+        &self,
+        name: &str,
+        input: &str,
+        body: &str,
+        article: &mut ArticleWithTags,
+    ) -> Result<String, Box<dyn Error>> {
+        match self.lookup(name) {
+            Some(plugin) => {
+                check_required_arguments(plugin, input)?;
+                plugin.exec_block(input, body, article)
+            }
+            None => Err(format!("Plugin '{}' is not supported", name).into()),
+        }
+    }
+
+    /// dependencies of a directive, lua plugins have none
+    pub fn dependencies(&self, name: &str, input: &str, article: &ArticleWithTags) -> Vec<PathBuf> {
+        match self.lookup(name) {
+            Some(plugin) => plugin.dependencies(input, article),
+            None => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::articles::plugins::{
+    parse_key_value_arguments, wrap_block, Plugin, PluginArgument, PluginOutput,
+};
+use crate::articles::utils;
+use crate::articles::ArticleWithTags;
+use std::error::Error;
+
+/// `[[!note type=warning title="careful"]] ... [[!/note]]` renders the body as a callout
+pub struct Note;
+
+const TYPES: [&str; 5] = ["note", "tip", "info", "warning", "danger"];
+
+fn note(input: &str, body: &str) -> Result<String, Box<dyn Error>> {
+    let arguments = parse_key_value_arguments("note", input, &["type", "title"])?;
+    let note_type = arguments.get("type").map_or("note", |t| t.as_str());
+    if !TYPES.contains(&note_type) {
+        return Err(format!(
+            "note: unknown type '{}', expected one of: {}",
+            note_type,
+            TYPES.join(", ")
+        )
+        .into());
+    }
+    // the type is the default title, e.g. "Warning"
+    let title = match arguments.get("title") {
+        Some(title) => title.clone(),
+        None => note_type[..1].to_uppercase() + &note_type[1..],
+    };
+    Ok(wrap_block(
+        &format!(
+            r#"<div class="pankat-note pankat-note-{}"><p class="pankat-note-title">{}</p>"#,
+            note_type,
+            utils::escape_html(&title)
+        ),
+        body,
+        "</div>",
+    ))
+}
+
+impl Plugin for Note {
+    fn name(&self) -> &str {
+        "note"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[
+            PluginArgument {
+                name: "type",
+                description: "note (default), tip, info, warning or danger",
+                required: false,
+            },
+            PluginArgument {
+                name: "title",
+                description: "the title of the callout, defaults to the type",
+                required: false,
+            },
+        ]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Html
+    }
+
+    fn exec(&self, _input: &str, _article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        Err("note: missing the closing [[!/note]]".into())
+    }
+
+    fn is_block(&self) -> bool {
+        true
+    }
+
+    fn exec_block(
+        &self,
+        input: &str,
+        body: &str,
+        _article: &mut ArticleWithTags,
+    ) -> Result<String, Box<dyn Error>> {
+        note(input, body)
+    }
+}
+
+#[test]
+fn test_note() {
+    assert_eq!(
+        note("type=warning", "\nbe *careful*\n").unwrap(),
+        "\n<div class=\"pankat-note pankat-note-warning\"><p class=\"pankat-note-title\">Warning</p>\n\nbe *careful*\n\n</div>\n"
+    );
+    assert!(note(r#"title="<b>""#, "x")
+        .unwrap()
+        .contains(r#"<p class="pankat-note-title">&lt;b&gt;</p>"#));
+    assert!(note("type=fancy", "x").is_err());
+}
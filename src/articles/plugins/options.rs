@@ -0,0 +1,167 @@
+use crate::articles::plugins::arguments::parse_arguments;
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
+use crate::articles::ArticleWithTags;
+use crate::renderer::pandoc::MATH_MODES;
+use std::error::Error;
+
+/// `[[!options notoc noanchors nolive numbered=false math=katex]]` overrides the site wide
+/// rendering defaults from pankat.toml for one article
+pub struct Options;
+
+const OPTIONS: &str = "toc, notoc, anchors, noanchors, live, nolive, numbered=true, numbered=false, math=none, math=mathml, math=katex";
+
+fn set(
+    name: &'static str,
+    field: &mut Option<bool>,
+    value: bool,
+    seen: &mut Vec<&'static str>,
+) -> Result<(), Box<dyn Error>> {
+    match *field {
+        Some(previous) if previous != value => {
+            if seen.contains(&name) {
+                Err(format!(
+                    "options: '{}' is both enabled and disabled, remove one of them",
+                    name
+                )
+                .into())
+            } else {
+                Err(format!(
+                    "options: '{}' is already set differently by another [[!options]] directive",
+                    name
+                )
+                .into())
+            }
+        }
+        _ => {
+            seen.push(name);
+            *field = Some(value);
+            Ok(())
+        }
+    }
+}
+
+fn set_math(
+    article: &mut ArticleWithTags,
+    value: &str,
+    seen: &mut Vec<&'static str>,
+) -> Result<(), Box<dyn Error>> {
+    match &article.math {
+        Some(previous) if previous != value => {
+            if seen.contains(&"math") {
+                Err("options: 'math' is set twice, remove one of them".into())
+            } else {
+                Err(
+                    "options: 'math' is already set differently by another [[!options]] directive"
+                        .into(),
+                )
+            }
+        }
+        _ => {
+            seen.push("math");
+            article.math = Some(value.to_string());
+            Ok(())
+        }
+    }
+}
+
+impl Plugin for Options {
+    fn name(&self) -> &str {
+        "options"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[PluginArgument {
+            name: "options",
+            description:
+                "toc, notoc, anchors, noanchors, live, nolive, numbered=true|false or math=none|mathml|katex",
+            required: true,
+        }]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        let mut seen: Vec<&'static str> = Vec::new();
+        let arguments = parse_arguments(input).map_err(|e| format!("options: {}", e))?;
+        for argument in arguments.0 {
+            let option = match argument.key {
+                Some(key) => format!("{}={}", key, argument.value),
+                None => argument.value,
+            };
+            match option.to_lowercase().as_str() {
+                "toc" => set("toc", &mut article.tocify, true, &mut seen)?,
+                "notoc" => set("toc", &mut article.tocify, false, &mut seen)?,
+                "anchors" => set("anchors", &mut article.anchorjs, true, &mut seen)?,
+                "noanchors" => set("anchors", &mut article.anchorjs, false, &mut seen)?,
+                "live" => set("live", &mut article.live_updates, true, &mut seen)?,
+                "nolive" => set("live", &mut article.live_updates, false, &mut seen)?,
+                "numbered" | "numbered=true" => {
+                    set("numbered", &mut article.numbered, true, &mut seen)?
+                }
+                "numbered=false" => set("numbered", &mut article.numbered, false, &mut seen)?,
+                math if math
+                    .strip_prefix("math=")
+                    .is_some_and(|mode| MATH_MODES.contains(&mode)) =>
+                {
+                    set_math(article, &math["math=".len()..], &mut seen)?
+                }
+                _ => {
+                    return Err(format!(
+                        "options: unknown option '{}', expected one of: {}",
+                        option, OPTIONS
+                    )
+                    .into())
+                }
+            }
+        }
+        Ok(String::new())
+    }
+}
+
+#[test]
+fn test_options() {
+    let mut article = ArticleWithTags {
+        src_file_name: "posts/short.mdwn".to_string(),
+        dst_file_name: String::new(),
+        ..Default::default()
+    };
+
+    Options
+        .exec("notoc noanchors numbered=false", &mut article)
+        .unwrap();
+    assert_eq!(article.tocify, Some(false));
+    assert_eq!(article.anchorjs, Some(false));
+    assert_eq!(article.live_updates, None);
+    assert_eq!(article.numbered, Some(false));
+
+    // repeating an option in another directive is fine, contradicting it is not
+    Options.exec("notoc nolive", &mut article).unwrap();
+    assert_eq!(article.live_updates, Some(false));
+    assert_eq!(
+        Options.exec("toc", &mut article).unwrap_err().to_string(),
+        "options: 'toc' is already set differently by another [[!options]] directive"
+    );
+
+    article.anchorjs = None;
+    assert_eq!(
+        Options
+            .exec("anchors noanchors", &mut article)
+            .unwrap_err()
+            .to_string(),
+        "options: 'anchors' is both enabled and disabled, remove one of them"
+    );
+    assert!(Options.exec("nosidebar", &mut article).is_err());
+
+    Options.exec("math=KaTeX", &mut article).unwrap();
+    assert_eq!(article.math.as_deref(), Some("katex"));
+    assert_eq!(
+        Options
+            .exec("math=mathml", &mut article)
+            .unwrap_err()
+            .to_string(),
+        "options: 'math' is already set differently by another [[!options]] directive"
+    );
+    assert!(Options.exec("math=latex", &mut article).is_err());
+}
@@ -0,0 +1,139 @@
+use crate::articles::front_matter::parse_date;
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
+use crate::articles::ArticleWithTags;
+use chrono::NaiveDateTime;
+use std::error::Error;
+
+/// `[[!publish_at 2026-11-01 08:00]]` hides the article like a draft until the date has passed,
+/// the running server publishes it then
+pub struct PublishAt;
+
+/// `[[!expire_at 2027-01-01]]` hides the article again once the date has passed
+pub struct ExpireAt;
+
+fn date(name: &str, input: &str) -> Result<NaiveDateTime, Box<dyn Error>> {
+    let value = input.trim().trim_matches('"').trim();
+    parse_date(value).ok_or_else(|| {
+        format!(
+            "{}: unsupported date '{}', use YYYY-MM-DD HH:MM",
+            name, value
+        )
+        .into()
+    })
+}
+
+fn set(
+    name: &str,
+    field: &mut Option<NaiveDateTime>,
+    value: NaiveDateTime,
+) -> Result<(), Box<dyn Error>> {
+    match field {
+        Some(previous) if *previous != value => Err(format!(
+            "{}: already set to {} by another [[!{}]] directive",
+            name, previous, name
+        )
+        .into()),
+        _ => {
+            *field = Some(value);
+            Ok(())
+        }
+    }
+}
+
+/// an article expiring before it is published would never be visible
+pub fn check_schedule(article: &ArticleWithTags) -> Result<(), Box<dyn Error>> {
+    if let (Some(publish_at), Some(expire_at)) = (article.publish_at, article.expire_at) {
+        if expire_at <= publish_at {
+            return Err(format!(
+                "expire_at {} is not after publish_at {}",
+                expire_at, publish_at
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+impl Plugin for PublishAt {
+    fn name(&self) -> &str {
+        "publish_at"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[PluginArgument {
+            name: "date",
+            description: "the article is published at this date, YYYY-MM-DD HH:MM",
+            required: true,
+        }]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        set(
+            "publish_at",
+            &mut article.publish_at,
+            date("publish_at", input)?,
+        )?;
+        check_schedule(article).map_err(|e| format!("publish_at: {}", e))?;
+        Ok(String::new())
+    }
+}
+
+impl Plugin for ExpireAt {
+    fn name(&self) -> &str {
+        "expire_at"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[PluginArgument {
+            name: "date",
+            description: "the article is hidden again at this date, YYYY-MM-DD HH:MM",
+            required: true,
+        }]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        set(
+            "expire_at",
+            &mut article.expire_at,
+            date("expire_at", input)?,
+        )?;
+        check_schedule(article).map_err(|e| format!("expire_at: {}", e))?;
+        Ok(String::new())
+    }
+}
+
+#[test]
+fn test_schedule() {
+    let mut article = ArticleWithTags {
+        src_file_name: "posts/scheduled.mdwn".to_string(),
+        dst_file_name: String::new(),
+        ..Default::default()
+    };
+
+    PublishAt.exec("2026-11-01 08:00", &mut article).unwrap();
+    assert_eq!(article.publish_at, parse_date("2026-11-01 08:00"));
+    PublishAt
+        .exec("\"2026-11-01 08:00\"", &mut article)
+        .unwrap();
+    assert!(PublishAt.exec("2026-11-02", &mut article).is_err());
+    assert!(ExpireAt.exec("next week", &mut article).is_err());
+
+    assert_eq!(
+        ExpireAt
+            .exec("2026-10-01", &mut article)
+            .unwrap_err()
+            .to_string(),
+        "expire_at: expire_at 2026-10-01 00:00:00 is not after publish_at 2026-11-01 08:00:00"
+    );
+    article.expire_at = None;
+    ExpireAt.exec("2027-01-01", &mut article).unwrap();
+    assert_eq!(article.expire_at, parse_date("2027-01-01"));
+}
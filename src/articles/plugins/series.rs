@@ -1,12 +1,32 @@
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
 use crate::articles::ArticleWithTags;
 use std::error::Error;
 
-pub fn series(input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
-    if input.contains('\n') || input.contains('\t') {
-        Err("Argument contains invalid characters (newlines or tabs)".into())
-    } else {
-        let series = input.to_string();
-        article.series = Some(series);
-        Ok("".to_string())
+pub struct Series;
+
+impl Plugin for Series {
+    fn name(&self) -> &str {
+        "series"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[PluginArgument {
+            name: "series",
+            description: "the name of the series this article belongs to",
+            required: true,
+        }]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        if input.contains('\n') || input.contains('\t') {
+            Err("Argument contains invalid characters (newlines or tabs)".into())
+        } else {
+            article.series = Some(input.to_string());
+            Ok("".to_string())
+        }
     }
 }
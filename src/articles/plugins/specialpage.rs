@@ -1,7 +1,24 @@
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
 use crate::articles::ArticleWithTags;
 use std::error::Error;
 
-pub fn specialpage(_input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
-    article.special_page = Some(true);
-    Ok("".to_string())
+pub struct SpecialPage;
+
+impl Plugin for SpecialPage {
+    fn name(&self) -> &str {
+        "specialpage"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, _input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        article.special_page = Some(true);
+        Ok("".to_string())
+    }
 }
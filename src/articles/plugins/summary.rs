@@ -1,12 +1,32 @@
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
 use crate::articles::ArticleWithTags;
 use std::error::Error;
 
-pub fn summary(input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
-    if input.contains('\n') || input.contains('\t') {
-        Err("Argument contains invalid characters (newlines or tabs)".into())
-    } else {
-        let summary = input.trim().to_string();
-        article.summary = Some(summary);
-        Ok("".to_string())
+pub struct Summary;
+
+impl Plugin for Summary {
+    fn name(&self) -> &str {
+        "summary"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[PluginArgument {
+            name: "summary",
+            description: "a short summary shown in the timeline",
+            required: true,
+        }]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        if input.contains('\n') || input.contains('\t') {
+            Err("Argument contains invalid characters (newlines or tabs)".into())
+        } else {
+            article.summary = Some(input.trim().to_string());
+            Ok("".to_string())
+        }
     }
 }
@@ -1,12 +1,42 @@
+use crate::articles::plugins::arguments::parse_arguments;
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
 use crate::articles::ArticleWithTags;
 use std::error::Error;
 
-pub fn tag(input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
-    if input.contains('\n') || input.contains('\t') {
-        Err("Argument contains invalid characters (newlines or tabs)".into())
-    } else {
-        let tag = input.to_string();
-        article.tags = Some(tag.split_whitespace().map(|s| s.to_string()).collect());
+pub struct Tag;
+
+impl Plugin for Tag {
+    fn name(&self) -> &str {
+        "tag"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[PluginArgument {
+            name: "tags",
+            description: "whitespace separated list of tags",
+            required: true,
+        }]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        let arguments = parse_arguments(input).map_err(|e| format!("tag: {}", e))?;
+        arguments
+            .expect_keys(&[])
+            .map_err(|e| format!("tag: {}", e))?;
+        let mut tags: Vec<String> = Vec::new();
+        for tag in arguments.positional() {
+            if tag.is_empty() || tag.contains(char::is_whitespace) {
+                return Err(
+                    format!("tag: '{}' is not a valid tag, tags are single words", tag).into(),
+                );
+            }
+            tags.push(tag.to_string());
+        }
+        article.tags = Some(tags);
         Ok("".to_string())
     }
 }
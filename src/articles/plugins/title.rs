@@ -1,12 +1,32 @@
+use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput};
 use crate::articles::ArticleWithTags;
 use std::error::Error;
 
-pub fn title(input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
-    if input.contains('\n') || input.contains('\t') {
-        Err("Argument contains invalid characters (newlines or tabs)".into())
-    } else {
-        let title = input.trim().to_string();
-        article.title = Some(title);
-        Ok("".to_string())
+pub struct Title;
+
+impl Plugin for Title {
+    fn name(&self) -> &str {
+        "title"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[PluginArgument {
+            name: "title",
+            description: "the article title, defaults to the file name",
+            required: true,
+        }]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Metadata
+    }
+
+    fn exec(&self, input: &str, article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        if input.contains('\n') || input.contains('\t') {
+            Err("Argument contains invalid characters (newlines or tabs)".into())
+        } else {
+            article.title = Some(input.trim().to_string());
+            Ok("".to_string())
+        }
     }
 }
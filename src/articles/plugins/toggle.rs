@@ -0,0 +1,85 @@
+use crate::articles::plugins::{
+    parse_key_value_arguments, wrap_block, Plugin, PluginArgument, PluginOutput,
+};
+use crate::articles::utils;
+use crate::articles::ArticleWithTags;
+use std::error::Error;
+
+/// `[[!toggle title="details" open=true]] ... [[!/toggle]]` renders the body as a collapsible
+/// section
+pub struct Toggle;
+
+fn toggle(input: &str, body: &str) -> Result<String, Box<dyn Error>> {
+    let arguments = parse_key_value_arguments("toggle", input, &["title", "open"])?;
+    let title = arguments.get("title").map_or("details", |t| t.as_str());
+    let open = match arguments.get("open").map(|o| o.as_str()) {
+        None | Some("false") => "",
+        Some("true") => " open",
+        Some(open) => {
+            return Err(format!("toggle: open must be true or false, got '{}'", open).into())
+        }
+    };
+    Ok(wrap_block(
+        &format!(
+            r#"<details class="pankat-toggle"{}><summary>{}</summary>"#,
+            open,
+            utils::escape_html(title)
+        ),
+        body,
+        "</details>",
+    ))
+}
+
+impl Plugin for Toggle {
+    fn name(&self) -> &str {
+        "toggle"
+    }
+
+    fn arguments(&self) -> &'static [PluginArgument] {
+        &[
+            PluginArgument {
+                name: "title",
+                description: "the always visible summary, defaults to 'details'",
+                required: false,
+            },
+            PluginArgument {
+                name: "open",
+                description: "open=true shows the body initially",
+                required: false,
+            },
+        ]
+    }
+
+    fn output(&self) -> PluginOutput {
+        PluginOutput::Html
+    }
+
+    fn exec(&self, _input: &str, _article: &mut ArticleWithTags) -> Result<String, Box<dyn Error>> {
+        Err("toggle: missing the closing [[!/toggle]]".into())
+    }
+
+    fn is_block(&self) -> bool {
+        true
+    }
+
+    fn exec_block(
+        &self,
+        input: &str,
+        body: &str,
+        _article: &mut ArticleWithTags,
+    ) -> Result<String, Box<dyn Error>> {
+        toggle(input, body)
+    }
+}
+
+#[test]
+fn test_toggle() {
+    assert_eq!(
+        toggle(r#"title="the log" open=true"#, "```\nlog\n```").unwrap(),
+        "\n<details class=\"pankat-toggle\" open><summary>the log</summary>\n\n```\nlog\n```\n\n</details>\n"
+    );
+    assert!(toggle("", "x")
+        .unwrap()
+        .contains("<summary>details</summary>"));
+    assert!(toggle("open=yes", "x").is_err());
+}
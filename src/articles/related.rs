@@ -0,0 +1,255 @@
+use crate::articles::ArticleWithTags;
+use crate::config;
+use crate::db::cache::get_cache;
+use crate::db::related::{
+    get_related_articles, set_all_related_articles, set_related_articles, RelatedArticle,
+};
+use diesel::prelude::*;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+/// share of the tag overlap in the score, the rest is the similarity of the text
+const TAG_WEIGHT: f64 = 0.5;
+/// shorter words are mostly stop words
+const MIN_WORD_LEN: usize = 3;
+
+/// an article for the similarity, the terms are tf-idf weighted with a length of 1
+struct Document {
+    src_file_name: String,
+    tags: HashSet<String>,
+    terms: HashMap<String, f64>,
+}
+
+/// counts the words of the text in the html
+fn term_counts(html: &str) -> HashMap<String, usize> {
+    let tags = Regex::new(r"(?s)<[^>]*>|&[a-zA-Z#0-9]+;").unwrap();
+    let text = tags.replace_all(html, " ");
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_WORD_LEN)
+        .filter(|word| !word.chars().all(|c| c.is_numeric()))
+    {
+        *counts.entry(word.to_lowercase()).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn documents(articles: Vec<(ArticleWithTags, String)>) -> Vec<Document> {
+    let counts: Vec<HashMap<String, usize>> =
+        articles.iter().map(|(_, html)| term_counts(html)).collect();
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for terms in counts.iter() {
+        for term in terms.keys() {
+            *document_frequency.entry(term.as_str()).or_insert(0) += 1;
+        }
+    }
+    let document_count = articles.len() as f64;
+    let weighted: Vec<HashMap<String, f64>> = counts
+        .iter()
+        .map(|terms| {
+            let mut weights: HashMap<String, f64> = terms
+                .iter()
+                .map(|(term, count)| {
+                    let idf = (document_count / document_frequency[term.as_str()] as f64).ln();
+                    (term.clone(), (1.0 + (*count as f64).ln()) * idf)
+                })
+                .filter(|(_, weight)| *weight > 0.0)
+                .collect();
+            let length = weights.values().map(|w| w * w).sum::<f64>().sqrt();
+            if length > 0.0 {
+                weights.values_mut().for_each(|w| *w /= length);
+            }
+            weights
+        })
+        .collect();
+    articles
+        .into_iter()
+        .zip(weighted)
+        .map(|((article, _), terms)| Document {
+            src_file_name: article.src_file_name,
+            tags: article.tags.unwrap_or_default().into_iter().collect(),
+            terms,
+        })
+        .collect()
+}
+
+/// the jaccard index of the tags combined with the cosine similarity of the terms
+fn similarity(a: &Document, b: &Document) -> f64 {
+    let tags = if a.tags.is_empty() && b.tags.is_empty() {
+        0.0
+    } else {
+        a.tags.intersection(&b.tags).count() as f64 / a.tags.union(&b.tags).count() as f64
+    };
+    let (small, large) = if a.terms.len() < b.terms.len() {
+        (&a.terms, &b.terms)
+    } else {
+        (&b.terms, &a.terms)
+    };
+    let text: f64 = small
+        .iter()
+        .filter_map(|(term, weight)| large.get(term).map(|other| weight * other))
+        .sum();
+    TAG_WEIGHT * tags + (1.0 - TAG_WEIGHT) * text
+}
+
+/// the `limit` most similar documents
+fn related_to(document: &Document, documents: &[Document], limit: usize) -> Vec<RelatedArticle> {
+    let mut related: Vec<RelatedArticle> = documents
+        .iter()
+        .filter(|other| other.src_file_name != document.src_file_name)
+        .map(|other| RelatedArticle {
+            src_file_name: document.src_file_name.clone(),
+            related_src_file_name: other.src_file_name.clone(),
+            score: similarity(document, other),
+        })
+        .filter(|related| related.score > 0.0)
+        .collect();
+    related.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.related_src_file_name.cmp(&b.related_src_file_name))
+    });
+    related.truncate(limit);
+    related
+}
+
+/// the visible articles, without special pages, with their cached html
+fn load_documents(conn: &mut SqliteConnection) -> Vec<Document> {
+    let articles = match crate::db::article::get_visible_articles(conn) {
+        Ok(articles) => articles,
+        Err(e) => {
+            println!("Error: loading articles for related articles failed: {}", e);
+            return Vec::new();
+        }
+    };
+    let articles: Vec<(ArticleWithTags, String)> = articles
+        .into_iter()
+        .filter_map(|article| {
+            get_cache(conn, article.src_file_name.clone())
+                .map(|cache_entry| (article, cache_entry.html))
+        })
+        .collect();
+    documents(articles)
+}
+
+fn src_file_names(related: &[RelatedArticle]) -> Vec<&str> {
+    related
+        .iter()
+        .map(|r| r.related_src_file_name.as_str())
+        .collect()
+}
+
+/// computes the related articles of all articles, done when the articles are built
+pub fn update_all_related_articles(conn: &mut SqliteConnection) {
+    let limit = config::Config::get().related_articles;
+    let documents = if limit > 0 {
+        load_documents(conn)
+    } else {
+        Vec::new()
+    };
+    let related: Vec<RelatedArticle> = documents
+        .iter()
+        .flat_map(|document| related_to(document, &documents, limit))
+        .collect();
+    if let Err(e) = set_all_related_articles(conn, &related) {
+        println!("Error: updating related articles failed: {}", e);
+    }
+}
+
+/// recomputes the related articles of a changed, added or removed article and of the articles
+/// the change affects, returns the latter if their list changed
+pub fn update_related_articles(
+    conn: &mut SqliteConnection,
+    src_file_name: &str,
+) -> HashSet<String> {
+    let limit = config::Config::get().related_articles;
+    let mut changed: HashSet<String> = HashSet::new();
+    if limit == 0 {
+        return changed;
+    }
+    let documents = load_documents(conn);
+    let document = documents
+        .iter()
+        .find(|document| document.src_file_name == src_file_name);
+    let related = match document {
+        Some(document) => related_to(document, &documents, limit),
+        // hidden, removed or a special page
+        None => Vec::new(),
+    };
+    if let Err(e) = set_related_articles(conn, src_file_name, &related) {
+        println!("Error: updating related articles failed: {}", e);
+    }
+
+    for other in documents.iter() {
+        if other.src_file_name == src_file_name {
+            continue;
+        }
+        let current: Vec<RelatedArticle> =
+            get_related_articles(conn, &other.src_file_name).unwrap_or_default();
+        let score = document.map_or(0.0, |document| similarity(other, document));
+        // only the score to the changed article differs, the others are recomputed when it was
+        // or now is one of their related articles
+        let affected = current
+            .iter()
+            .any(|r| r.related_src_file_name == src_file_name)
+            || (score > 0.0
+                && (current.len() < limit || current.last().is_some_and(|r| score > r.score)));
+        if !affected {
+            continue;
+        }
+        let related = related_to(other, &documents, limit);
+        if src_file_names(&related) != src_file_names(&current) {
+            changed.insert(other.src_file_name.clone());
+        }
+        if let Err(e) = set_related_articles(conn, &other.src_file_name, &related) {
+            println!("Error: updating related articles failed: {}", e);
+        }
+    }
+    changed
+}
+
+#[test]
+fn test_similarity() {
+    let html = r#"<p>Nix on <b>Windows</b> &amp; nix-shell, 2024 on windows</p>"#;
+    let counts = term_counts(html);
+    assert_eq!(counts.get("nix"), Some(&2));
+    assert_eq!(counts.get("windows"), Some(&2));
+    assert_eq!(counts.get("shell"), Some(&1));
+    assert_eq!(counts.get("on"), None);
+    assert_eq!(counts.get("2024"), None);
+    assert_eq!(counts.get("amp"), None);
+
+    let article = |name: &str, tags: &[&str]| ArticleWithTags {
+        src_file_name: format!("posts/{}.mdwn", name),
+        dst_file_name: format!("{}.html", name),
+        tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+        ..Default::default()
+    };
+    let documents = documents(vec![
+        (
+            article("libnix", &["nix", "windows"]),
+            "<p>cross compiling nix packages for windows with mingw</p>".to_string(),
+        ),
+        (
+            article("mingw", &["nix", "windows"]),
+            "<p>the mingw toolchain builds windows binaries</p>".to_string(),
+        ),
+        (
+            article("flakes", &["nix"]),
+            "<p>nix flakes pin the packages</p>".to_string(),
+        ),
+        (
+            article("rust", &["rust"]),
+            "<p>async traits in rust</p>".to_string(),
+        ),
+    ]);
+    let related = related_to(&documents[0], &documents, 2);
+    assert_eq!(
+        src_file_names(&related),
+        vec!["posts/mingw.mdwn", "posts/flakes.mdwn"]
+    );
+    assert!(related[0].score > related[1].score);
+    assert!(related_to(&documents[3], &documents, 2).is_empty());
+    assert_eq!(related_to(&documents[0], &documents, 1).len(), 1);
+}
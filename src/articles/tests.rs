@@ -1,42 +1,26 @@
 #[cfg(test)]
 mod tests {
     use crate::articles::eval_plugins;
+    use crate::articles::exec_plugin;
+    use crate::articles::plugins::{Plugin, PluginArgument, PluginOutput, PluginRegistry};
     use crate::articles::ArticleWithTags;
+    use std::error::Error;
 
     #[test]
     fn test_title() {
         let input = "hi!\n[[!title Test Title  ]]\n".to_string();
         let expected_output = "hi!\n\n".to_string();
         let mut article = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let article_expected = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
             title: Some("Test Title".to_string()),
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let result = eval_plugins(&input, &mut article);
@@ -60,35 +44,16 @@ mod tests {
         let input = "hi!\n[[!draft]]\n".to_string();
         let expected_output = "hi!\n\n".to_string();
         let mut article = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let article_expected = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
             draft: Some(true),
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let result = eval_plugins(&input, &mut article);
@@ -112,35 +77,16 @@ mod tests {
         let input = "hi!\n[[!specialpage]]\n".to_string();
         let expected_output = "hi!\n\n".to_string();
         let mut article = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let article_expected = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
             special_page: Some(true),
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let result = eval_plugins(&input, &mut article);
@@ -167,38 +113,19 @@ mod tests {
         let input = "hi!\n[[!meta date=\"2024-07-19 14:33\"]]\n".to_string();
         let expected_output = "hi!\n\n".to_string();
         let mut article = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let parsed_time =
             NaiveDateTime::parse_from_str("2024-07-19 14:33", "%Y-%m-%d %H:%M").unwrap();
 
         let article_expected = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
             modification_date: Some(parsed_time),
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let result = eval_plugins(&input, &mut article);
@@ -223,35 +150,16 @@ mod tests {
         let input = "hi!\n[[!series   asdf ]]\n".to_string();
         let expected_output = "hi!\n\n".to_string();
         let mut article = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let article_expected = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
             series: Some("asdf".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let result = eval_plugins(&input, &mut article);
@@ -276,35 +184,16 @@ mod tests {
         let input = "hi!\n[[!tag   foo bar asdf]]\n".to_string();
         let expected_output = "hi!\n\n".to_string();
         let mut article = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let article_expected = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
             tags: vec!["foo".to_string(), "bar".to_string(), "asdf".to_string()].into(),
+            ..Default::default()
         };
 
         let result = eval_plugins(&input, &mut article);
@@ -329,35 +218,16 @@ mod tests {
         let input = "hi!\n[[!summary   foo bar asdf  ]]\n".to_string();
         let expected_output = "hi!\n\n".to_string();
         let mut article = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let article_expected = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
             summary: Some("foo bar asdf".to_string()),
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let result = eval_plugins(&input, &mut article);
@@ -379,51 +249,34 @@ mod tests {
 
     #[test]
     fn test_img() {
+        // not indented, 4 spaces would make it an indented code block
         let input = r#"
-        hi!
-        [[!img media/nlnet-logo.gif class="noFancy" style="float: right"]]
-        abab
-        [[!img posts/libnix/Nix_snowflake_windows.svg class="noFancy" style="float: right" width="200px"]]
-        "#.to_string();
+hi!
+[[!img media/nlnet-logo.gif class="noFancy" style="float: right"]]
+abab
+[[!img posts/libnix/Nix_snowflake_windows.svg class="noFancy" style="float: right" width="200px"]]
+"#
+        .to_string();
 
         let expected_output = r#"
-        hi!
-        <a href="media/nlnet-logo.gif"><img src="media/nlnet-logo.gif" class="noFancy" style="float: right"></a>
-        abab
-        <a href="posts/libnix/Nix_snowflake_windows.svg"><img src="posts/libnix/Nix_snowflake_windows.svg" class="noFancy" style="float: right" width="200px"></a>
-        "#.to_string();
+hi!
+<a href="media/nlnet-logo.gif"><img src="media/nlnet-logo.gif" class="noFancy" style="float: right"></a>
+abab
+<a href="posts/libnix/Nix_snowflake_windows.svg"><img src="posts/libnix/Nix_snowflake_windows.svg" class="noFancy" style="float: right" width="200px"></a>
+"#
+        .to_string();
 
         //o := `<a href="` + f[1] + `"><img src=` + b + `></a>`
         let mut article = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let article_expected = ArticleWithTags {
-            id: None,
             src_file_name: "example.mdwn".to_string(),
             dst_file_name: String::new(),
-            title: None,
-            modification_date: None,
-            summary: None,
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
 
         let result = eval_plugins(&input, &mut article);
@@ -442,4 +295,258 @@ mod tests {
         println!("article_expected: {:#?}", article_expected);
         assert_eq!(article, article_expected);
     }
+
+    #[test]
+    fn test_unknown_plugin_lists_available_plugins() {
+        let mut article = ArticleWithTags {
+            src_file_name: "example.mdwn".to_string(),
+            dst_file_name: String::new(),
+            ..Default::default()
+        };
+
+        let result = exec_plugin("[[!foo bar]]", &mut article);
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("Plugin 'foo' is not supported"));
+        assert!(error.contains(
+            "bibliography, code, column, columns, draft, expire_at, gallery, graphviz, img, include, inline, link, mermaid, meta, note, options, plantuml, publish_at, series, specialpage, summary, tag, title, toggle"
+        ));
+    }
+
+    #[test]
+    fn test_plugin_registry_register() {
+        struct Shout;
+
+        impl Plugin for Shout {
+            fn name(&self) -> &str {
+                "shout"
+            }
+
+            fn arguments(&self) -> &'static [PluginArgument] {
+                &[PluginArgument {
+                    name: "text",
+                    description: "the text to shout",
+                    required: true,
+                }]
+            }
+
+            fn output(&self) -> PluginOutput {
+                PluginOutput::Markdown
+            }
+
+            fn exec(
+                &self,
+                input: &str,
+                _article: &mut ArticleWithTags,
+            ) -> Result<String, Box<dyn Error>> {
+                Ok(format!("**{}**", input.to_uppercase()))
+            }
+        }
+
+        let mut article = ArticleWithTags {
+            src_file_name: "example.mdwn".to_string(),
+            dst_file_name: String::new(),
+            ..Default::default()
+        };
+
+        let mut registry = PluginRegistry::with_builtin_plugins();
+        assert!(registry.register(Box::new(Shout)).is_ok());
+        assert!(registry.register(Box::new(Shout)).is_err());
+
+        let result = registry.exec("Shout", "hello", &mut article);
+        assert_eq!(result.unwrap(), "**HELLO**");
+
+        let result = registry.exec("shout", "", &mut article);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("requires argument 'text'"));
+
+        let result = registry.exec("title", "Test Title", &mut article);
+        assert_eq!(result.unwrap(), "");
+        assert_eq!(article.title, Some("Test Title".to_string()));
+    }
+
+    #[test]
+    fn test_lua_plugin() {
+        use crate::articles::plugins::lua::run_script;
+
+        let script = r#"
+            function render(args, article)
+              article.title = "Lua " .. args
+              table.insert(article.tags, "lua")
+              article.modification_date = "2024-04-12 20:53"
+              return "**" .. args .. "**"
+            end
+        "#;
+
+        let mut article = ArticleWithTags {
+            src_file_name: "example.mdwn".to_string(),
+            dst_file_name: String::new(),
+            tags: Some(vec!["foo".to_string()]),
+            ..Default::default()
+        };
+
+        let result = run_script("test", script, "hello", &mut article);
+        assert_eq!(result.unwrap(), "**hello**");
+        assert_eq!(article.title, Some("Lua hello".to_string()));
+        assert_eq!(
+            article.tags,
+            Some(vec!["foo".to_string(), "lua".to_string()])
+        );
+        assert_eq!(
+            article.modification_date,
+            chrono::NaiveDateTime::parse_from_str("2024-04-12 20:53", "%Y-%m-%d %H:%M").ok()
+        );
+
+        let result = run_script("test", "x = 1", "hello", &mut article);
+        assert!(result.is_err());
+
+        let script = r#"
+            function render(args, article, params)
+              return params[1] .. "|" .. params.alt
+            end
+        "#;
+        let result = run_script("test", script, r#"foo.jpg alt="a cat""#, &mut article);
+        assert_eq!(result.unwrap(), "foo.jpg|a cat");
+    }
+
+    #[test]
+    fn test_eval_plugins_block_directives() {
+        let mut article = ArticleWithTags {
+            src_file_name: "example.mdwn".to_string(),
+            dst_file_name: String::new(),
+            ..Default::default()
+        };
+
+        let input = "[[!note type=tip]]\nouter\n[[!note]]inner [[!tag nix]][[!/note]]\n[[!toggle title=log]]\nlog\n[[!/toggle]]\n[[!/note]]\n";
+        let output = eval_plugins(&input.to_string(), &mut article).unwrap();
+        assert_eq!(
+            output,
+            concat!(
+                "\n<div class=\"pankat-note pankat-note-tip\"><p class=\"pankat-note-title\">Tip</p>\n\n",
+                "outer\n",
+                "\n<div class=\"pankat-note pankat-note-note\"><p class=\"pankat-note-title\">Note</p>\n\ninner \n\n</div>\n",
+                "\n",
+                "\n<details class=\"pankat-toggle\"><summary>log</summary>\n\nlog\n\n</details>\n",
+                "\n</div>\n\n"
+            )
+        );
+        assert_eq!(article.tags, Some(vec!["nix".to_string()]));
+
+        // unbalanced blocks are left as they are
+        let input = "[[!note]] open\n[[!/toggle]]\n".to_string();
+        assert_eq!(eval_plugins(&input, &mut article).unwrap(), input);
+    }
+
+    #[test]
+    fn test_eval_plugins_skips_code() {
+        let mut article = ArticleWithTags {
+            src_file_name: "example.mdwn".to_string(),
+            dst_file_name: String::new(),
+            ..Default::default()
+        };
+
+        let input = "use `[[!tag foo]]` or\n\n```\n[[!tag bar]]\n```\n\n    [[!title code]]\n\nnot \\[[!tag escaped]] but [[!tag real]]\n".to_string();
+        let output = eval_plugins(&input, &mut article).unwrap();
+        assert_eq!(
+            output,
+            "use `[[!tag foo]]` or\n\n```\n[[!tag bar]]\n```\n\n    [[!title code]]\n\nnot \\[[!tag escaped]] but \n"
+        );
+        assert_eq!(article.tags, Some(vec!["real".to_string()]));
+        assert_eq!(article.title, None);
+    }
+
+    #[test]
+    fn test_eval_plugins_quoted_arguments() {
+        let mut article = ArticleWithTags {
+            src_file_name: "example.mdwn".to_string(),
+            dst_file_name: String::new(),
+            ..Default::default()
+        };
+
+        let input = "a [[!img foo.jpg alt=\"a ]] b\"]] [[!meta description=\"\"\"two\nlines\"\"\"]] [[!tag x\n]]";
+        let output = eval_plugins(&input.to_string(), &mut article).unwrap();
+        assert_eq!(
+            output,
+            "a <a href=\"foo.jpg\"><img src=\"foo.jpg\" alt=\"a ]] b\"></a>  [[!tag x\n]]"
+        );
+        assert_eq!(article.description, Some("two\nlines".to_string()));
+        assert_eq!(article.tags, None);
+
+        let output = eval_plugins(
+            &"[[!title 12\" vinyl record]] [[!summary he said \"hi]]".to_string(),
+            &mut article,
+        )
+        .unwrap();
+        assert!(!output.contains("[[!"));
+        assert_eq!(article.title, Some("12\" vinyl record".to_string()));
+        assert_eq!(article.summary, Some("he said \"hi".to_string()));
+    }
+
+    #[test]
+    fn test_lua_plugin_fallback() {
+        let dir = std::env::temp_dir().join("pankat_test_lua_plugin_fallback");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("hello.lua"),
+            "function render(args, article) return 'hello ' .. args end",
+        )
+        .unwrap();
+
+        let mut article = ArticleWithTags {
+            src_file_name: "example.mdwn".to_string(),
+            dst_file_name: String::new(),
+            ..Default::default()
+        };
+
+        let mut registry = PluginRegistry::with_builtin_plugins();
+        registry.set_lua_plugins_dir(dir.clone());
+
+        assert!(registry.names().contains(&"hello".to_string()));
+        let result = registry.exec("hello", "world", &mut article);
+        assert_eq!(result.unwrap(), "hello world");
+
+        let error = registry.exec("nope", "", &mut article).unwrap_err();
+        assert!(error.to_string().contains("toggle, hello"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_scheduled_neighbours() {
+        use crate::articles::neighbours_by_date;
+        use chrono::{Duration, NaiveDateTime};
+
+        let article = |name: &str, date: &str| ArticleWithTags {
+            src_file_name: format!("{}.mdwn", name),
+            dst_file_name: format!("{}.html", name),
+            modification_date: NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").ok(),
+            ..Default::default()
+        };
+        let visible = vec![
+            article("a", "2024-01-01 10:00"),
+            article("c", "2024-01-03 10:00"),
+            article("d", "2024-01-04 10:00"),
+        ];
+
+        let mut scheduled = article("b", "2024-01-02 10:00");
+        let now = NaiveDateTime::parse_from_str("2026-10-18 12:00", "%Y-%m-%d %H:%M").unwrap();
+        scheduled.publish_at = Some(now + Duration::minutes(1));
+        assert!(scheduled.is_hidden(now));
+        assert!(!scheduled.is_hidden(now + Duration::minutes(1)));
+        scheduled.expire_at = Some(now + Duration::days(1));
+        assert!(scheduled.is_hidden(now + Duration::days(1)));
+
+        let neighbours: Vec<&str> = neighbours_by_date(&visible, &scheduled)
+            .iter()
+            .map(|a| a.dst_file_name.as_str())
+            .collect();
+        assert_eq!(neighbours, vec!["a.html", "c.html"]);
+        let neighbours: Vec<&str> = neighbours_by_date(&visible, &visible[2])
+            .iter()
+            .map(|a| a.dst_file_name.as_str())
+            .collect();
+        assert_eq!(neighbours, vec!["c.html"]);
+    }
 }
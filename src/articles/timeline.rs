@@ -27,6 +27,12 @@ struct MetaData {
     series: HashMap<String, Vec<usize>>,
     #[serde(rename = "Years")]
     years: HashMap<usize, Vec<usize>>,
+    /// article id to the estimated reading time in minutes
+    #[serde(rename = "ReadingTime")]
+    reading_time: HashMap<usize, i32>,
+    /// article id to the word count, code blocks excluded
+    #[serde(rename = "WordCount")]
+    word_count: HashMap<usize, i32>,
 }
 
 impl MetaData {
@@ -34,6 +40,8 @@ impl MetaData {
         let mut tags_map: HashMap<String, Vec<usize>> = HashMap::new();
         let mut series_map: HashMap<String, Vec<usize>> = HashMap::new();
         let mut years_map: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut reading_time_map: HashMap<usize, i32> = HashMap::new();
+        let mut word_count_map: HashMap<usize, i32> = HashMap::new();
 
         for article in articles {
             let year = match article.modification_date {
@@ -49,6 +57,13 @@ impl MetaData {
                 .or_insert_with(Vec::new)
                 .push(article.id.unwrap() as usize);
 
+            if let Some(reading_time) = article.reading_time {
+                reading_time_map.insert(article.id.unwrap() as usize, reading_time);
+            }
+            if let Some(word_count) = article.word_count {
+                word_count_map.insert(article.id.unwrap() as usize, word_count);
+            }
+
             match &article.tags {
                 Some(tags) => {
                     for tag in tags {
@@ -76,6 +91,8 @@ impl MetaData {
             tags: tags_map,
             series: series_map,
             years: years_map,
+            reading_time: reading_time_map,
+            word_count: word_count_map,
         }
     }
 }
@@ -246,6 +263,7 @@ fn generate_article_references_by_year(articles: &Vec<ArticleWithTags>) -> Strin
               <div class="timeline-title">{{article_title}}</div>
               <div class="floo">
                 <div class="timeline-title-timestamp">{{article_date}}</div>
+                {{#reading_time}}<div class="timeline-reading-time">{{reading_time}} min read, {{word_count}} words</div>{{/reading_time}}
                 <p class="tag">{{{tagToLinkList}}}{{{seriesToLinkList}}}</p>
                 <a href="{{{Subdir}}}/{{dst_file_name}}" style="flex: 1;">open complete article</a>
               </div>
@@ -263,6 +281,8 @@ fn generate_article_references_by_year(articles: &Vec<ArticleWithTags>) -> Strin
             "article_title": article.title,
             "article_date": crate::renderer::utils::date_and_time(&article.modification_date),
             "summary": article.summary,
+            "reading_time": article.reading_time.filter(|minutes| *minutes > 0),
+            "word_count": article.word_count,
             "dst_file_name": article.dst_file_name,
             "tagToLinkList": tag_to_link_list(article),
             "seriesToLinkList": series_to_link_list(article.series.clone()),
@@ -345,6 +365,20 @@ fn create_filter_control(articles: &Vec<ArticleWithTags>) -> String {
         ));
     }
     page_content.push_str("</p>");
+
+    // the buckets are resolved with MetaData.ReadingTime in pankat-timeline.js
+    page_content.push_str("<p id=\"lengthCloud\">");
+    for (length, label) in [
+        ("short", "short reads"),
+        ("medium", "5 to 15 minutes"),
+        ("long", "long reads"),
+    ] {
+        page_content.push_str(&format!(
+            "<a class=\"lengthbtn btn btn-primary\" onClick=\"setFilter('length::{}', 1)\">{}</a>",
+            length, label
+        ));
+    }
+    page_content.push_str("</p>");
     page_content.push_str("</div></div></div></div>");
     page_content
 }
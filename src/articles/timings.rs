@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// articles listed in the summary of `build_articles`
+const SLOWEST_ARTICLES: usize = 10;
+
+/// how long the steps of an article took during the initial scan and build
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ArticleTiming {
+    /// front matter and plugins
+    pub parse: Duration,
+    /// the renderer, `None` if the html was cached
+    pub render: Option<Duration>,
+    /// templates and writing the file
+    pub write: Option<Duration>,
+}
+
+impl ArticleTiming {
+    pub fn total(&self) -> Duration {
+        self.parse + self.render.unwrap_or_default() + self.write.unwrap_or_default()
+    }
+}
+
+/// collected by `scan_articles`, completed and printed by `build_articles`
+#[derive(Debug, Default)]
+pub struct BuildTimings {
+    pub articles: HashMap<String, ArticleTiming>,
+    /// the time the articles were rendered in parallel
+    pub render: Duration,
+    pub workers: usize,
+}
+
+impl BuildTimings {
+    pub fn article(&mut self, src_file_name: &str) -> &mut ArticleTiming {
+        self.articles.entry(src_file_name.to_string()).or_default()
+    }
+
+    /// the articles which took longest, slowest first
+    fn slowest(&self, count: usize) -> Vec<(&str, &ArticleTiming)> {
+        let mut articles: Vec<(&str, &ArticleTiming)> = self
+            .articles
+            .iter()
+            .map(|(src_file_name, timing)| (src_file_name.as_str(), timing))
+            .collect();
+        articles.sort_by(|a, b| b.1.total().cmp(&a.1.total()).then(a.0.cmp(b.0)));
+        articles.truncate(count);
+        articles
+    }
+
+    pub fn print_summary(&self) {
+        let rendered: Vec<Duration> = self.articles.values().filter_map(|t| t.render).collect();
+        let parse: Duration = self.articles.values().map(|t| t.parse).sum();
+        let write: Duration = self.articles.values().filter_map(|t| t.write).sum();
+        println!("====== Build summary ======");
+        println!(
+            "{} articles, {} rendered and {} from the cache",
+            self.articles.len(),
+            rendered.len(),
+            self.articles.len() - rendered.len()
+        );
+        println!("Parsing: {:?}", parse);
+        println!(
+            "Rendering: {:?} with {} workers, {:?} of renderer time",
+            self.render,
+            self.workers,
+            rendered.iter().sum::<Duration>()
+        );
+        println!("Writing: {:?}", write);
+        if self.articles.is_empty() {
+            return;
+        }
+        println!("Slowest articles:");
+        for (src_file_name, timing) in self.slowest(SLOWEST_ARTICLES) {
+            println!(
+                "  {:>10.2?} {} (parse {:.2?}, render {}, write {})",
+                timing.total(),
+                src_file_name,
+                timing.parse,
+                timing
+                    .render
+                    .map_or("cached".to_string(), |render| format!("{:.2?}", render)),
+                timing
+                    .write
+                    .map_or("-".to_string(), |write| format!("{:.2?}", write))
+            );
+        }
+    }
+}
+
+#[test]
+fn test_slowest() {
+    let mut timings = BuildTimings::default();
+    timings.article("posts/cached.mdwn").parse = Duration::from_millis(5);
+    *timings.article("posts/pandoc.mdwn") = ArticleTiming {
+        parse: Duration::from_millis(5),
+        render: Some(Duration::from_millis(300)),
+        write: Some(Duration::from_millis(10)),
+    };
+    timings.article("posts/draft.mdwn").parse = Duration::from_millis(5);
+    timings.article("posts/cached.mdwn").write = Some(Duration::from_millis(20));
+
+    assert_eq!(
+        timings.article("posts/pandoc.mdwn").total(),
+        Duration::from_millis(315)
+    );
+    let slowest: Vec<&str> = timings
+        .slowest(2)
+        .into_iter()
+        .map(|(src_file_name, _)| src_file_name)
+        .collect();
+    assert_eq!(slowest, vec!["posts/pandoc.mdwn", "posts/cached.mdwn"]);
+}
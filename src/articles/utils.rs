@@ -1,8 +1,8 @@
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub fn position_to_line_and_col_number(
-    article_mdwn_raw_string: &String,
+    article_mdwn_raw_string: &str,
     position: usize,
 ) -> Result<(usize, usize), Box<dyn Error>> {
     if position >= article_mdwn_raw_string.len() {
@@ -31,6 +31,55 @@ pub fn position_to_line_and_col_number(
     Err("Failed to backtrack position".into())
 }
 
+pub fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn unescape_html(s: &str) -> String {
+    s.replace("&gt;", ">")
+        .replace("&lt;", "<")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// resolves a path used in a directive of `src_file_name` against the input, `input/media` and
+/// the directory of the article, in that order, paths leading out of the input are refused
+///
+/// the path is canonical, `relative_to_input` turns it into the path stored as a dependency.
+pub fn find_input_path(input: &Path, src_file_name: &str, path: &str) -> Option<PathBuf> {
+    if path.contains("://") || path.starts_with("//") || path.starts_with("data:") {
+        return None;
+    }
+    let input_dir = input.canonicalize().ok()?;
+    let path = path.trim_start_matches('/');
+    let article_dir: PathBuf = Path::new(src_file_name)
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_default();
+    [
+        input.join(path),
+        input.join("media").join(path),
+        input.join(article_dir).join(path),
+    ]
+    .into_iter()
+    .filter_map(|p| p.canonicalize().ok())
+    .find(|canonical| canonical.starts_with(&input_dir))
+}
+
+/// a path found by `find_input_path` relative to the input, e.g. `posts/snippets/a.md` for
+/// `[[!include ../snippets/a.md]]`, like the paths the file monitor reports
+pub fn relative_to_input(input: &Path, path: &Path) -> PathBuf {
+    input
+        .canonicalize()
+        .ok()
+        .and_then(|input_dir| path.strip_prefix(input_dir).ok().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
 pub fn article_src_file_name_to_title(article_src_file_name: &PathBuf) -> String {
     // Convert PathBuf to string and handle error
     let file_name_str = article_src_file_name
@@ -78,3 +127,50 @@ fn test_create_dst_file_name() {
     let dst_file_name = create_dst_file_name(&article_path, flat);
     assert_eq!(dst_file_name, "src/articles/test.html");
 }
+
+#[test]
+fn test_find_input_path() {
+    let dir = std::env::temp_dir().join(format!("pankat-test-input-path-{}", std::process::id()));
+    let input = dir.join("input");
+    std::fs::create_dir_all(input.join("media")).unwrap();
+    std::fs::create_dir_all(input.join("posts")).unwrap();
+    std::fs::write(input.join("media/cat.jpg"), b"").unwrap();
+    std::fs::write(input.join("posts/code.rs"), b"").unwrap();
+    std::fs::write(dir.join("secret.rs"), b"").unwrap();
+    let input_dir = input.canonicalize().unwrap();
+
+    assert_eq!(
+        find_input_path(&input, "posts/a.mdwn", "cat.jpg"),
+        Some(input_dir.join("media/cat.jpg"))
+    );
+    assert_eq!(
+        find_input_path(&input, "posts/a.mdwn", "code.rs"),
+        Some(input_dir.join("posts/code.rs"))
+    );
+    assert_eq!(
+        find_input_path(&input, "posts/a.mdwn", "../secret.rs"),
+        None
+    );
+    assert_eq!(
+        find_input_path(&input, "posts/a.mdwn", "/../secret.rs"),
+        None
+    );
+    assert_eq!(
+        find_input_path(&input, "posts/a.mdwn", "../../secret.rs"),
+        None
+    );
+    assert_eq!(find_input_path(&input, "posts/a.mdwn", "../.."), None);
+    assert_eq!(
+        find_input_path(&input, "posts/a.mdwn", "posts/../media/cat.jpg"),
+        Some(input_dir.join("media/cat.jpg"))
+    );
+    assert_eq!(
+        relative_to_input(
+            &input,
+            &find_input_path(&input, "posts/a.mdwn", "../media/cat.jpg").unwrap()
+        ),
+        PathBuf::from("media/cat.jpg")
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,71 @@
+use regex::Regex;
+
+/// words per minute of prose
+const WORDS_PER_MINUTE: usize = 200;
+/// code is read slower than prose
+const CODE_WORDS_PER_MINUTE: usize = 100;
+
+/// words of the rendered article, the words in code blocks are counted separately
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct WordCount {
+    pub words: usize,
+    pub code_words: usize,
+}
+
+impl WordCount {
+    /// estimated reading time in minutes, at least a minute for any text
+    pub fn reading_time(&self) -> usize {
+        let minutes = self.words as f64 / WORDS_PER_MINUTE as f64
+            + self.code_words as f64 / CODE_WORDS_PER_MINUTE as f64;
+        minutes.ceil() as usize
+    }
+}
+
+/// `separator` replaces the tags, the highlighting of code blocks splits words into spans
+fn count(html: &str, separator: &str) -> usize {
+    let tags = Regex::new(r"(?s)<[^>]*>|&[a-zA-Z#0-9]+;").unwrap();
+    tags.replace_all(html, separator)
+        .split_whitespace()
+        .filter(|word| word.chars().any(|c| c.is_alphanumeric()))
+        .count()
+}
+
+/// counts the words in the html pandoc rendered
+pub fn count_words(html: &str) -> WordCount {
+    let code_blocks = Regex::new(r"(?s)<pre[^>]*>.*?</pre>").unwrap();
+    WordCount {
+        words: count(&code_blocks.replace_all(html, " "), " "),
+        code_words: code_blocks
+            .find_iter(html)
+            .map(|code_block| count(code_block.as_str(), ""))
+            .sum(),
+    }
+}
+
+#[test]
+fn test_count_words() {
+    let html = concat!(
+        "<h1>Nix on Windows</h1>\n",
+        "<p>Run <code>nix-shell</code> &mdash; then build it.</p>\n",
+        r#"<div class="sourceCode"><pre class="sourceCode bash"><code class="sourceCode bash"><span class="ex">nix-build</span> <span class="at">-A</span> hello"#,
+        "\n</code></pre></div>"
+    );
+    let word_count = count_words(html);
+    assert_eq!(
+        word_count,
+        WordCount {
+            words: 8,
+            code_words: 3,
+        }
+    );
+    assert_eq!(word_count.reading_time(), 1);
+    assert_eq!(count_words("").reading_time(), 0);
+    assert_eq!(
+        WordCount {
+            words: 1000,
+            code_words: 150,
+        }
+        .reading_time(),
+        7
+    );
+}
@@ -62,6 +62,48 @@ pub struct Config {
     pub port: u16,
     pub static_build_only: bool,
     pub flat: bool,
+    /// site wide defaults for the `[[!options]]` directive
+    #[serde(default = "default_true")]
+    pub toc: bool,
+    #[serde(default = "default_true")]
+    pub anchors: bool,
+    #[serde(default = "default_true")]
+    pub live_updates: bool,
+    #[serde(default = "default_true")]
+    pub numbered: bool,
+    /// `pandoc` or `pulldown-cmark`, see `RENDERERS`
+    #[serde(default = "default_renderer")]
+    pub renderer: String,
+    /// articles rendered in parallel during the initial scan, 0 uses all cpus
+    #[serde(default)]
+    pub jobs: usize,
+    /// how pandoc renders `$...$`, one of `MATH_MODES`
+    #[serde(default = "default_math")]
+    pub math: String,
+    /// site wide `[[!bibliography]]`, relative to the input
+    #[serde(default)]
+    pub bibliography: Option<String>,
+    #[serde(default)]
+    pub csl: Option<String>,
+    /// length of the "you might also like" list below the articles, 0 disables it
+    #[serde(default = "default_related_articles")]
+    pub related_articles: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_renderer() -> String {
+    "pandoc".to_string()
+}
+
+fn default_math() -> String {
+    "mathml".to_string()
+}
+
+fn default_related_articles() -> usize {
+    5
 }
 
 enum OnlyDefaultValues {
@@ -211,6 +253,11 @@ impl Config {
         SINGLETON.get().expect("Config not initialized")
     }
 
+    /// like get() but None before the config was initialized, e.g. in unit tests
+    pub fn try_get() -> Option<&'static Arc<Config>> {
+        SINGLETON.get()
+    }
+
     pub fn initialize(config: Config) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         #[cfg(all(not(test)))]
         {
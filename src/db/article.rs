@@ -14,11 +14,43 @@ use crate::db::schema::article_tags::dsl::article_tags as article_tags_table;
 
 use diesel::dsl::sql;
 use diesel::prelude::*;
-use diesel::sql_types::Nullable;
+use diesel::sql_types::{Bool, Nullable};
+use diesel::sqlite::Sqlite;
 
 use chrono::NaiveDateTime;
 use std::collections::HashSet;
 
+/// `[[!publish_at]]` and `[[!expire_at]]` dates are local time, like the `[[!meta]]` dates
+pub fn now() -> NaiveDateTime {
+    chrono::Local::now().naive_local()
+}
+
+/// the filter for published articles: no drafts, `publish_at` reached and `expire_at` not yet,
+/// the query counterpart of `ArticleWithTags::is_hidden`
+pub fn visible<QS: 'static>() -> Box<dyn BoxableExpression<QS, Sqlite, SqlType = Nullable<Bool>>>
+where
+    articles_objects::draft: SelectableExpression<QS>,
+    articles_objects::publish_at: SelectableExpression<QS>,
+    articles_objects::expire_at: SelectableExpression<QS>,
+{
+    let now = now();
+    Box::new(
+        articles_objects::draft
+            .eq(false)
+            .or(articles_objects::draft.is_null())
+            .and(
+                articles_objects::publish_at
+                    .is_null()
+                    .or(articles_objects::publish_at.le(now)),
+            )
+            .and(
+                articles_objects::expire_at
+                    .is_null()
+                    .or(articles_objects::expire_at.gt(now)),
+            ),
+    )
+}
+
 #[derive(Queryable, Insertable, Identifiable, Selectable, Debug, Clone, PartialEq)]
 #[diesel(table_name = schema::articles)]
 pub struct Article {
@@ -34,6 +66,21 @@ pub struct Article {
     pub anchorjs: Option<bool>,
     pub tocify: Option<bool>,
     pub live_updates: Option<bool>,
+    pub numbered: Option<bool>,
+    pub author: Option<String>,
+    pub updated: Option<NaiveDateTime>,
+    pub lang: Option<String>,
+    pub canonical: Option<String>,
+    pub description: Option<String>,
+    pub keywords: Option<String>,
+    pub publish_at: Option<NaiveDateTime>,
+    pub expire_at: Option<NaiveDateTime>,
+    pub math: Option<String>,
+    pub bibliography: Option<String>,
+    pub csl: Option<String>,
+    pub word_count: Option<i32>,
+    pub code_word_count: Option<i32>,
+    pub reading_time: Option<i32>,
 }
 
 impl From<Article> for ArticleWithTags {
@@ -51,6 +98,21 @@ impl From<Article> for ArticleWithTags {
             anchorjs: article.anchorjs,
             tocify: article.tocify,
             live_updates: article.live_updates,
+            numbered: article.numbered,
+            author: article.author,
+            updated: article.updated,
+            lang: article.lang,
+            canonical: article.canonical,
+            description: article.description,
+            keywords: article.keywords,
+            publish_at: article.publish_at,
+            expire_at: article.expire_at,
+            math: article.math,
+            bibliography: article.bibliography,
+            csl: article.csl,
+            word_count: article.word_count,
+            code_word_count: article.code_word_count,
+            reading_time: article.reading_time,
             tags: None,
         }
     }
@@ -121,7 +183,6 @@ pub fn get_article_with_tags_by_id(
     }
 }
 
-#[allow(dead_code)]
 pub fn get_article_with_tags_by_src_file_name(
     conn: &mut SqliteConnection,
     src_file_name: String,
@@ -148,11 +209,7 @@ pub fn get_most_recent_article(
     conn: &mut SqliteConnection,
 ) -> Result<Option<ArticleWithTags>, diesel::result::Error> {
     let res = articles_table
-        .filter(
-            articles_objects::draft
-                .eq(false)
-                .or(articles_objects::draft.is_null()),
-        )
+        .filter(visible())
         .filter(
             articles_objects::special_page
                 .eq(false)
@@ -178,6 +235,41 @@ pub fn get_most_recent_article(
     }
 }
 
+/// the visible articles whose `src_file_name` or title is LIKE one of `patterns`, LIKE ignores
+/// the case and `_` and `%` are wildcards, so callers check the articles again
+pub fn get_visible_articles_like(
+    conn: &mut SqliteConnection,
+    patterns: &[String],
+) -> Result<Vec<ArticleWithTags>, diesel::result::Error> {
+    type Matching = Box<dyn BoxableExpression<articles_table, Sqlite, SqlType = Nullable<Bool>>>;
+    let like = |pattern: &String| -> Matching {
+        Box::new(
+            articles_objects::src_file_name
+                .like(pattern.clone())
+                .or(articles_objects::title.like(pattern.clone())),
+        )
+    };
+    let Some((first, rest)) = patterns.split_first() else {
+        return Ok(Vec::new());
+    };
+    let matching: Matching = rest.iter().fold(like(first), |matching, pattern| {
+        Box::new(matching.or(like(pattern)))
+    });
+    let articles: Vec<Article> = articles_table
+        .filter(visible())
+        .filter(matching)
+        .load(conn)?;
+    articles
+        .into_iter()
+        .map(|article| {
+            let tags = get_tags_for_article(conn, article.id)?;
+            let mut article_with_tags: ArticleWithTags = article.into();
+            article_with_tags.tags = tags;
+            Ok(article_with_tags)
+        })
+        .collect()
+}
+
 pub fn get_all_articles(
     conn: &mut SqliteConnection,
 ) -> Result<Vec<ArticleWithTags>, diesel::result::Error> {
@@ -211,11 +303,7 @@ pub fn get_visible_articles(
 ) -> Result<Vec<ArticleWithTags>, diesel::result::Error> {
     // FIXME rewrite most functions to this return type
     let articles_query = articles_table
-        .filter(
-            articles_objects::draft
-                .eq(false)
-                .or(articles_objects::draft.is_null()),
-        )
+        .filter(visible())
         .filter(
             articles_objects::special_page
                 .eq(false)
@@ -245,18 +333,13 @@ pub fn get_visible_articles(
     }
 }
 
-#[allow(dead_code)]
 pub fn get_visible_articles_by_series(
     conn: &mut SqliteConnection,
     series: &str,
 ) -> Result<Vec<ArticleWithTags>, diesel::result::Error> {
     let res = articles_table
         .filter(articles_objects::series.eq(series))
-        .filter(
-            articles_objects::draft
-                .eq(false)
-                .or(articles_objects::draft.is_null()),
-        )
+        .filter(visible())
         .filter(
             articles_objects::special_page
                 .eq(false)
@@ -286,7 +369,6 @@ pub fn get_visible_articles_by_series(
     }
 }
 
-#[allow(dead_code)]
 pub fn get_visible_articles_by_tag(
     conn: &mut SqliteConnection,
     tag: String,
@@ -297,11 +379,7 @@ pub fn get_visible_articles_by_tag(
         )
         .inner_join(tags_table.on(article_tags_objects::tag_id.eq(tags_objects::id)))
         .filter(tags_objects::name.eq(tag))
-        .filter(
-            articles_objects::draft
-                .eq(false)
-                .or(articles_objects::draft.is_null()),
-        )
+        .filter(visible())
         .select(articles_table::all_columns())
         .load::<Article>(conn);
     match res {
@@ -323,12 +401,12 @@ pub fn get_visible_articles_by_tag(
     }
 }
 
-#[allow(dead_code)]
+/// drafts and articles which are not published yet or expired
 pub fn get_drafts(
     conn: &mut SqliteConnection,
 ) -> Result<Vec<ArticleWithTags>, diesel::result::Error> {
     let res = articles_table
-        .filter(articles_objects::draft.eq(true))
+        .filter(diesel::dsl::not(visible()))
         .order((
             sql::<Nullable<diesel::sql_types::Timestamp>>("modification_date IS NULL"),
             articles_objects::modification_date.asc(),
@@ -353,6 +431,47 @@ pub fn get_drafts(
     }
 }
 
+/// the next `publish_at` or `expire_at` date after `after`, the server rebuilds then
+pub fn get_next_schedule_change(
+    conn: &mut SqliteConnection,
+    after: NaiveDateTime,
+) -> Result<Option<NaiveDateTime>, diesel::result::Error> {
+    let publish_at: Option<NaiveDateTime> = articles_table
+        .filter(articles_objects::publish_at.gt(after))
+        .select(diesel::dsl::min(articles_objects::publish_at))
+        .first(conn)?;
+    let expire_at: Option<NaiveDateTime> = articles_table
+        .filter(articles_objects::expire_at.gt(after))
+        .select(diesel::dsl::min(articles_objects::expire_at))
+        .first(conn)?;
+    Ok(publish_at.into_iter().chain(expire_at).min())
+}
+
+/// articles which were published or expired in the interval (`from`, `to`]
+pub fn get_articles_with_schedule_change(
+    conn: &mut SqliteConnection,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Vec<ArticleWithTags>, diesel::result::Error> {
+    let res = articles_table
+        .filter(
+            articles_objects::publish_at
+                .gt(from)
+                .and(articles_objects::publish_at.le(to))
+                .or(articles_objects::expire_at
+                    .gt(from)
+                    .and(articles_objects::expire_at.le(to))),
+        )
+        .load::<Article>(conn)?;
+    let mut articles_out: Vec<ArticleWithTags> = Vec::new();
+    for article in res {
+        let mut article_with_tags: ArticleWithTags = article.clone().into();
+        article_with_tags.tags = get_tags_for_article(conn, article.id)?;
+        articles_out.push(article_with_tags);
+    }
+    Ok(articles_out)
+}
+
 #[allow(dead_code)]
 pub fn get_special_pages(
     conn: &mut SqliteConnection,
@@ -697,11 +816,7 @@ pub fn get_all_series_from_visible_articles(
 ) -> Result<Vec<String>, diesel::result::Error> {
     let res: QueryResult<Vec<Article>> = articles_table
         .filter(articles_objects::series.is_not_null())
-        .filter(
-            articles_objects::draft
-                .eq(false)
-                .or(articles_objects::draft.is_null()),
-        )
+        .filter(visible())
         .order((
             sql::<Nullable<diesel::sql_types::Timestamp>>("modification_date IS NULL"),
             articles_objects::modification_date.asc(),
@@ -852,11 +967,7 @@ pub fn get_prev_and_next_article(
     id: i32,
 ) -> Result<ArticleNeighbours, diesel::result::Error> {
     let articles_query: QueryResult<Vec<Article>> = articles_table
-        .filter(
-            articles_objects::draft
-                .eq(false)
-                .or(articles_objects::draft.is_null()),
-        )
+        .filter(visible())
         .filter(
             articles_objects::special_page
                 .eq(false)
@@ -891,11 +1002,7 @@ pub fn get_prev_and_next_article_for_series(
                 //println!("------------- {} -----------", series);
                 let articles_query: QueryResult<Vec<Article>> = articles_table
                     .filter(articles_objects::series.eq(series))
-                    .filter(
-                        articles_objects::draft
-                            .eq(false)
-                            .or(articles_objects::draft.is_null()),
-                    )
+                    .filter(visible())
                     .filter(
                         articles_objects::special_page
                             .eq(false)
@@ -29,6 +29,11 @@ pub fn get_cache_src_file_names(
     }
 }
 
+/// the html of all cache entries
+pub fn get_cache_htmls(conn: &mut SqliteConnection) -> Result<Vec<String>, diesel::result::Error> {
+    cache_table.select(cache_objects::html).load::<String>(conn)
+}
+
 pub fn del_cache_by_id(conn: &mut SqliteConnection, id: i32) -> Result<(), diesel::result::Error> {
     let ret = diesel::delete(cache_table.filter(cache_objects::id.eq(id))).execute(conn);
 
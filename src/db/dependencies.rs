@@ -0,0 +1,79 @@
+use crate::db::schema;
+use crate::db::schema::article_dependencies::dsl as article_dependencies_objects;
+use crate::db::schema::article_dependencies::dsl::article_dependencies as article_dependencies_table;
+
+use diesel::prelude::*;
+
+/// a file or directory, relative to the input, an article is generated from
+#[derive(Queryable, Insertable, Selectable, Clone, Debug, Eq, PartialEq, Hash)]
+#[diesel(table_name = schema::article_dependencies)]
+pub struct ArticleDependency {
+    pub src_file_name: String,
+    pub path: String,
+}
+
+/// replaces all dependencies of an article
+pub fn set_dependencies(
+    conn: &mut SqliteConnection,
+    src_file_name: String,
+    paths: &[String],
+) -> Result<(), diesel::result::Error> {
+    conn.transaction(|conn| {
+        diesel::delete(
+            article_dependencies_table
+                .filter(article_dependencies_objects::src_file_name.eq(&src_file_name)),
+        )
+        .execute(conn)?;
+        for path in paths {
+            diesel::insert_into(article_dependencies_table)
+                .values(ArticleDependency {
+                    src_file_name: src_file_name.clone(),
+                    path: path.clone(),
+                })
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+}
+
+pub fn del_dependencies_by_src_file_name(
+    conn: &mut SqliteConnection,
+    src_file_name: String,
+) -> Result<(), diesel::result::Error> {
+    diesel::delete(
+        article_dependencies_table
+            .filter(article_dependencies_objects::src_file_name.eq(src_file_name)),
+    )
+    .execute(conn)
+    .map(|_| ())
+}
+
+pub fn get_dependencies_src_file_names(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<String>, diesel::result::Error> {
+    article_dependencies_table
+        .select(article_dependencies_objects::src_file_name)
+        .distinct()
+        .load::<String>(conn)
+}
+
+/// src_file_names of the articles depending on `path` or on a directory containing it
+pub fn get_dependents(
+    conn: &mut SqliteConnection,
+    path: &str,
+) -> Result<Vec<String>, diesel::result::Error> {
+    let dependencies: Vec<ArticleDependency> = article_dependencies_table
+        .order(article_dependencies_objects::src_file_name.asc())
+        .load::<ArticleDependency>(conn)?;
+    let mut dependents: Vec<String> = dependencies
+        .into_iter()
+        .filter(|dependency| {
+            path == dependency.path
+                || path.starts_with(&format!("{}/", dependency.path.trim_end_matches('/')))
+        })
+        .map(|dependency| dependency.src_file_name)
+        .collect();
+    dependents.dedup();
+    Ok(dependents)
+}
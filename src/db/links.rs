@@ -0,0 +1,143 @@
+use crate::db::article::{visible, Article};
+use crate::db::schema;
+use crate::db::schema::article_links::dsl as article_links_objects;
+use crate::db::schema::article_links::dsl::article_links as article_links_table;
+use crate::db::schema::articles::dsl as articles_objects;
+use crate::db::schema::articles::dsl::articles as articles_table;
+
+use diesel::prelude::*;
+use diesel::sql_types::Bool;
+use diesel::sqlite::Sqlite;
+
+/// a `[[!link target]]` directive, target is a src_file_name, file name or title
+pub const LINK_KIND_DIRECTIVE: &str = "directive";
+/// a html link, target is a dst_file_name
+pub const LINK_KIND_HTML: &str = "html";
+/// an `[[!inline]]` directive, target is its query, e.g. `tags="nix" limit=10`
+pub const LINK_KIND_INLINE: &str = "inline";
+
+#[derive(Queryable, Insertable, Selectable, Clone, Debug, Eq, PartialEq, Hash)]
+#[diesel(table_name = schema::article_links)]
+pub struct ArticleLink {
+    pub src_file_name: String,
+    pub kind: String,
+    pub target: String,
+}
+
+pub fn get_links(
+    conn: &mut SqliteConnection,
+    src_file_name: String,
+) -> Result<Vec<ArticleLink>, diesel::result::Error> {
+    article_links_table
+        .filter(article_links_objects::src_file_name.eq(src_file_name))
+        .load::<ArticleLink>(conn)
+}
+
+/// replaces all links of an article
+pub fn set_links(
+    conn: &mut SqliteConnection,
+    src_file_name: String,
+    links: &[ArticleLink],
+) -> Result<(), diesel::result::Error> {
+    conn.transaction(|conn| {
+        diesel::delete(
+            article_links_table.filter(article_links_objects::src_file_name.eq(&src_file_name)),
+        )
+        .execute(conn)?;
+        for link in links {
+            diesel::insert_into(article_links_table)
+                .values(link)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+}
+
+pub fn del_links_by_src_file_name(
+    conn: &mut SqliteConnection,
+    src_file_name: String,
+) -> Result<(), diesel::result::Error> {
+    diesel::delete(
+        article_links_table.filter(article_links_objects::src_file_name.eq(src_file_name)),
+    )
+    .execute(conn)
+    .map(|_| ())
+}
+
+pub fn get_links_src_file_names(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<String>, diesel::result::Error> {
+    article_links_table
+        .select(article_links_objects::src_file_name)
+        .distinct()
+        .load::<String>(conn)
+}
+
+/// all links together with the article containing them, links from drafts and unpublished
+/// articles are skipped
+pub fn get_links_with_source(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<(ArticleLink, Article)>, diesel::result::Error> {
+    article_links_table
+        .inner_join(
+            articles_table
+                .on(articles_objects::src_file_name.eq(article_links_objects::src_file_name)),
+        )
+        .filter(visible())
+        .order((
+            articles_objects::modification_date.asc(),
+            articles_objects::src_file_name.asc(),
+        ))
+        .select((ArticleLink::as_select(), Article::as_select()))
+        .load::<(ArticleLink, Article)>(conn)
+}
+
+/// html links to `dst_file_name` and `[[!link]]` directives whose target is LIKE one of
+/// `directive_targets`, LIKE ignores the case and `_` matches any character, so callers check
+/// the directives with `link_matches`
+fn links_to<QS: 'static>(
+    dst_file_name: &str,
+    directive_targets: &[String],
+) -> Box<dyn BoxableExpression<QS, Sqlite, SqlType = Bool>>
+where
+    article_links_objects::kind: SelectableExpression<QS>,
+    article_links_objects::target: SelectableExpression<QS>,
+{
+    directive_targets.iter().fold(
+        Box::new(
+            article_links_objects::kind
+                .eq(LINK_KIND_HTML)
+                .and(article_links_objects::target.eq(dst_file_name.to_string())),
+        ),
+        |links, directive_target| {
+            Box::new(
+                links.or(article_links_objects::kind
+                    .eq(LINK_KIND_DIRECTIVE)
+                    .and(article_links_objects::target.like(directive_target.clone()))),
+            )
+        },
+    )
+}
+
+/// the links which might point to an article together with the article containing them,
+/// like `get_links_with_source` links from drafts and unpublished articles are skipped
+pub fn get_links_with_source_to(
+    conn: &mut SqliteConnection,
+    dst_file_name: &str,
+    directive_targets: &[String],
+) -> Result<Vec<(ArticleLink, Article)>, diesel::result::Error> {
+    article_links_table
+        .inner_join(
+            articles_table
+                .on(articles_objects::src_file_name.eq(article_links_objects::src_file_name)),
+        )
+        .filter(visible())
+        .filter(links_to(dst_file_name, directive_targets))
+        .order((
+            articles_objects::modification_date.asc(),
+            articles_objects::src_file_name.asc(),
+        ))
+        .select((ArticleLink::as_select(), Article::as_select()))
+        .load::<(ArticleLink, Article)>(conn)
+}
@@ -9,6 +9,9 @@ pub type DbPool = r2d2::Pool<ConnectionManager<SqliteConnection>>;
 
 pub mod article;
 pub mod cache;
+pub mod dependencies;
+pub mod links;
+pub mod related;
 pub mod schema;
 mod tests;
 pub mod users;
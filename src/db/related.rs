@@ -0,0 +1,83 @@
+use crate::db::article::{visible, Article};
+use crate::db::schema;
+use crate::db::schema::articles::dsl as articles_objects;
+use crate::db::schema::articles::dsl::articles as articles_table;
+use crate::db::schema::related_articles::dsl as related_articles_objects;
+use crate::db::schema::related_articles::dsl::related_articles as related_articles_table;
+
+use diesel::prelude::*;
+
+/// an article similar to `src_file_name`, a higher score is more similar
+#[derive(Queryable, Insertable, Selectable, Clone, Debug, PartialEq)]
+#[diesel(table_name = schema::related_articles)]
+pub struct RelatedArticle {
+    pub src_file_name: String,
+    pub related_src_file_name: String,
+    pub score: f64,
+}
+
+/// the related articles of an article, most similar first
+pub fn get_related_articles(
+    conn: &mut SqliteConnection,
+    src_file_name: &str,
+) -> Result<Vec<RelatedArticle>, diesel::result::Error> {
+    related_articles_table
+        .filter(related_articles_objects::src_file_name.eq(src_file_name))
+        .order((
+            related_articles_objects::score.desc(),
+            related_articles_objects::related_src_file_name.asc(),
+        ))
+        .load::<RelatedArticle>(conn)
+}
+
+/// replaces the related articles of an article
+pub fn set_related_articles(
+    conn: &mut SqliteConnection,
+    src_file_name: &str,
+    related: &[RelatedArticle],
+) -> Result<(), diesel::result::Error> {
+    conn.transaction(|conn| {
+        diesel::delete(
+            related_articles_table
+                .filter(related_articles_objects::src_file_name.eq(src_file_name)),
+        )
+        .execute(conn)?;
+        diesel::insert_into(related_articles_table)
+            .values(related)
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+/// replaces the related articles of all articles
+pub fn set_all_related_articles(
+    conn: &mut SqliteConnection,
+    related: &[RelatedArticle],
+) -> Result<(), diesel::result::Error> {
+    conn.transaction(|conn| {
+        diesel::delete(related_articles_table).execute(conn)?;
+        diesel::insert_into(related_articles_table)
+            .values(related)
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+/// the related articles which are visible, most similar first
+pub fn get_visible_related_articles(
+    conn: &mut SqliteConnection,
+    src_file_name: &str,
+) -> Result<Vec<Article>, diesel::result::Error> {
+    related_articles_table
+        .inner_join(articles_table.on(
+            articles_objects::src_file_name.eq(related_articles_objects::related_src_file_name),
+        ))
+        .filter(related_articles_objects::src_file_name.eq(src_file_name))
+        .filter(visible())
+        .order((
+            related_articles_objects::score.desc(),
+            related_articles_objects::related_src_file_name.asc(),
+        ))
+        .select(Article::as_select())
+        .load::<Article>(conn)
+}
@@ -1,5 +1,20 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    article_dependencies (src_file_name, path) {
+        src_file_name -> Text,
+        path -> Text,
+    }
+}
+
+diesel::table! {
+    article_links (src_file_name, kind, target) {
+        src_file_name -> Text,
+        kind -> Text,
+        target -> Text,
+    }
+}
+
 diesel::table! {
     article_tags (article_id, tag_id) {
         article_id -> Integer,
@@ -21,6 +36,21 @@ diesel::table! {
         anchorjs -> Nullable<Bool>,
         tocify -> Nullable<Bool>,
         live_updates -> Nullable<Bool>,
+        numbered -> Nullable<Bool>,
+        author -> Nullable<Text>,
+        updated -> Nullable<Timestamp>,
+        lang -> Nullable<Text>,
+        canonical -> Nullable<Text>,
+        description -> Nullable<Text>,
+        keywords -> Nullable<Text>,
+        publish_at -> Nullable<Timestamp>,
+        expire_at -> Nullable<Timestamp>,
+        math -> Nullable<Text>,
+        bibliography -> Nullable<Text>,
+        csl -> Nullable<Text>,
+        word_count -> Nullable<Integer>,
+        code_word_count -> Nullable<Integer>,
+        reading_time -> Nullable<Integer>,
     }
 }
 
@@ -33,6 +63,14 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    related_articles (src_file_name, related_src_file_name) {
+        src_file_name -> Text,
+        related_src_file_name -> Text,
+        score -> Double,
+    }
+}
+
 diesel::table! {
     tags (id) {
         id -> Integer,
@@ -52,4 +90,13 @@ diesel::table! {
 diesel::joinable!(article_tags -> articles (article_id));
 diesel::joinable!(article_tags -> tags (tag_id));
 
-diesel::allow_tables_to_appear_in_same_query!(article_tags, articles, cache, tags, users,);
+diesel::allow_tables_to_appear_in_same_query!(
+    article_dependencies,
+    article_links,
+    article_tags,
+    articles,
+    cache,
+    related_articles,
+    tags,
+    users,
+);
@@ -11,11 +11,9 @@ mod tests {
         let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
 
         let article_with_tags1 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set1.mdwn".to_string(),
             dst_file_name: "test_db_set1.html".to_string(),
             title: Some("Test".to_string()),
-            modification_date: None,
             summary: Some("Test".to_string()),
             tags: Some(vec![
                 "test1".to_string(),
@@ -23,47 +21,32 @@ mod tests {
                 "test3".to_string(),
             ]),
             series: Some("Test".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         set(&mut conn, &article_with_tags1).unwrap();
 
         let article_with_tags2 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set2.mdwn".to_string(),
             dst_file_name: "test_db_set2.html".to_string(),
             title: Some("Test2".to_string()),
-            modification_date: None,
             summary: Some("Test2".to_string()),
             tags: Some(vec!["test2".to_string(), "test3".to_string()]),
             series: Some("Test2".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         set(&mut conn, &article_with_tags2).unwrap();
 
         let article_with_tags3 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set3.mdwn".to_string(),
             dst_file_name: "test_db_set3.html".to_string(),
             title: Some("Test2".to_string()),
-            modification_date: None,
             summary: Some("Test2".to_string()),
             tags: Some(vec!["test2".to_string(), "test3".to_string()]),
             series: Some("Test2".to_string()),
             draft: Some(true),
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         set(&mut conn, &article_with_tags3).unwrap();
@@ -99,11 +82,9 @@ mod tests {
         let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
 
         let article_with_tags1 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set1.mdwn".to_string(),
             dst_file_name: "test_db_set1.html".to_string(),
             title: Some("Test".to_string()),
-            modification_date: None,
             summary: Some("Test".to_string()),
             tags: Some(vec![
                 "test1".to_string(),
@@ -111,47 +92,31 @@ mod tests {
                 "test3".to_string(),
             ]),
             series: Some("Test2".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         set(&mut conn, &article_with_tags1).unwrap();
 
         let article_with_tags2 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set2.mdwn".to_string(),
             dst_file_name: "test_db_set2.html".to_string(),
             title: Some("Test2".to_string()),
-            modification_date: None,
             summary: Some("Test2".to_string()),
             tags: Some(vec!["test2".to_string(), "test3".to_string()]),
             series: Some("Test2".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         set(&mut conn, &article_with_tags2).unwrap();
 
         let article_with_tags3 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set3.mdwn".to_string(),
             dst_file_name: "test_db_set3.html".to_string(),
             title: Some("Test2".to_string()),
-            modification_date: None,
             summary: Some("Test2".to_string()),
             tags: Some(vec!["test2".to_string(), "test3".to_string()]),
             series: Some("Test2".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         set(&mut conn, &article_with_tags3).unwrap();
@@ -159,7 +124,6 @@ mod tests {
         let parsed_time =
             NaiveDateTime::parse_from_str("2024-07-19 14:33", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags4 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set4.mdwn".to_string(),
             dst_file_name: "test_db_set4.html".to_string(),
             title: Some("Test2".to_string()),
@@ -167,11 +131,7 @@ mod tests {
             summary: Some("Test2".to_string()),
             tags: Some(vec!["test2".to_string(), "test3".to_string()]),
             series: Some("Test2".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         set(&mut conn, &article_with_tags4).unwrap();
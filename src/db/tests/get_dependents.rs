@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod tests {
+    use crate::db::dependencies::{get_dependents, set_dependencies};
+    use crate::db::tests::establish_connection_and_initialize_schema;
+    use diesel::sqlite::SqliteConnection;
+
+    #[test]
+    fn test_db_get_dependents() {
+        let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
+
+        set_dependencies(
+            &mut conn,
+            "posts/a.mdwn".to_string(),
+            &["media/cats".to_string()],
+        )
+        .unwrap();
+        set_dependencies(
+            &mut conn,
+            "posts/b.mdwn".to_string(),
+            &["media/cats/tom.jpg".to_string(), "posts/b.rs".to_string()],
+        )
+        .unwrap();
+
+        assert_eq!(
+            get_dependents(&mut conn, "media/cats/tom.jpg").unwrap(),
+            vec!["posts/a.mdwn".to_string(), "posts/b.mdwn".to_string()]
+        );
+        assert_eq!(
+            get_dependents(&mut conn, "media/cats").unwrap(),
+            vec!["posts/a.mdwn".to_string()]
+        );
+        assert_eq!(
+            get_dependents(&mut conn, "posts/b.rs").unwrap(),
+            vec!["posts/b.mdwn".to_string()]
+        );
+        assert!(get_dependents(&mut conn, "media/catsanddogs/x.jpg")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use crate::db::article::set;
+    use crate::db::links::{get_links_with_source, set_links, ArticleLink};
+    use crate::db::tests::establish_connection_and_initialize_schema;
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::articles::ArticleWithTags;
+
+    #[test]
+    fn test_db_get_links_with_source() {
+        let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
+
+        let article_with_tags1 = ArticleWithTags {
+            src_file_name: "foo/a.mdwn".to_string(),
+            dst_file_name: "a.html".to_string(),
+            title: Some("A".to_string()),
+            ..Default::default()
+        };
+        set(&mut conn, &article_with_tags1).unwrap();
+
+        let article_with_tags2 = ArticleWithTags {
+            src_file_name: "foo/b.mdwn".to_string(),
+            dst_file_name: "b.html".to_string(),
+            title: Some("B".to_string()),
+            draft: Some(true),
+            ..Default::default()
+        };
+        set(&mut conn, &article_with_tags2).unwrap();
+
+        let link_a = ArticleLink {
+            src_file_name: "foo/a.mdwn".to_string(),
+            kind: "html".to_string(),
+            target: "c.html".to_string(),
+        };
+        let link_b = ArticleLink {
+            src_file_name: "foo/b.mdwn".to_string(),
+            kind: "html".to_string(),
+            target: "c.html".to_string(),
+        };
+        set_links(
+            &mut conn,
+            "foo/a.mdwn".to_string(),
+            std::slice::from_ref(&link_a),
+        )
+        .unwrap();
+        set_links(&mut conn, "foo/b.mdwn".to_string(), &[link_b]).unwrap();
+
+        let links = get_links_with_source(&mut conn).unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].0, link_a);
+        assert_eq!(links[0].1.title, Some("A".to_string()));
+    }
+}
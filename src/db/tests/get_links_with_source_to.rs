@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use crate::db::article::set;
+    use crate::db::links::{get_links_with_source_to, set_links, ArticleLink};
+    use crate::db::tests::establish_connection_and_initialize_schema;
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::articles::ArticleWithTags;
+
+    fn link(src_file_name: &str, kind: &str, target: &str) -> ArticleLink {
+        ArticleLink {
+            src_file_name: src_file_name.to_string(),
+            kind: kind.to_string(),
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_db_get_links_with_source_to() {
+        let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
+
+        for name in ["a", "c"] {
+            let article = ArticleWithTags {
+                src_file_name: format!("posts/{}.mdwn", name),
+                dst_file_name: format!("{}.html", name),
+                title: Some(name.to_uppercase()),
+                ..Default::default()
+            };
+            set(&mut conn, &article).unwrap();
+        }
+        let links_a = [
+            link("posts/a.mdwn", "directive", "my post"),
+            link("posts/a.mdwn", "html", "my_post.html"),
+        ];
+        let links_c = [
+            link("posts/c.mdwn", "directive", "other"),
+            link("posts/c.mdwn", "html", "other.html"),
+        ];
+        set_links(&mut conn, "posts/a.mdwn".to_string(), &links_a).unwrap();
+        set_links(&mut conn, "posts/c.mdwn".to_string(), &links_c).unwrap();
+
+        let links = get_links_with_source_to(
+            &mut conn,
+            "my_post.html",
+            &["posts/my_post.mdwn".to_string(), "my_post".to_string()],
+        )
+        .unwrap();
+        let links: Vec<ArticleLink> = links.into_iter().map(|(link, _)| link).collect();
+        assert_eq!(links.len(), 2);
+        assert!(links.contains(&links_a[0]));
+        assert!(links.contains(&links_a[1]));
+
+        assert!(
+            get_links_with_source_to(&mut conn, "b.html", &["posts/b.mdwn".to_string()])
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use crate::db::article::{
+        get_articles_with_schedule_change, get_drafts, get_next_schedule_change,
+        get_prev_and_next_article, get_visible_articles, now, set,
+    };
+    use crate::db::tests::establish_connection_and_initialize_schema;
+    use chrono::{Duration, NaiveDateTime};
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::articles::ArticleWithTags;
+
+    fn article(
+        name: &str,
+        date: &str,
+        publish_at: Option<NaiveDateTime>,
+        expire_at: Option<NaiveDateTime>,
+    ) -> ArticleWithTags {
+        ArticleWithTags {
+            src_file_name: format!("posts/{}.mdwn", name),
+            dst_file_name: format!("{}.html", name),
+            title: Some(name.to_string()),
+            modification_date: NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").ok(),
+            publish_at,
+            expire_at,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_db_get_next_schedule_change() {
+        let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
+        let start = now();
+        let tomorrow = start + Duration::days(1);
+        let next_week = start + Duration::days(7);
+
+        let first = set(&mut conn, &article("first", "2024-01-01 10:00", None, None)).unwrap();
+        set(
+            &mut conn,
+            &article("scheduled", "2024-01-02 10:00", Some(tomorrow), None),
+        )
+        .unwrap();
+        let third = set(&mut conn, &article("third", "2024-01-03 10:00", None, None)).unwrap();
+        set(
+            &mut conn,
+            &article(
+                "expired",
+                "2024-01-04 10:00",
+                Some(start - Duration::days(2)),
+                Some(start - Duration::days(1)),
+            ),
+        )
+        .unwrap();
+        set(
+            &mut conn,
+            &article("expiring", "2024-01-05 10:00", None, Some(next_week)),
+        )
+        .unwrap();
+
+        let visible: Vec<String> = get_visible_articles(&mut conn)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.src_file_name)
+            .collect();
+        assert_eq!(
+            visible,
+            vec![
+                "posts/first.mdwn",
+                "posts/third.mdwn",
+                "posts/expiring.mdwn"
+            ]
+        );
+        let hidden: Vec<String> = get_drafts(&mut conn)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.src_file_name)
+            .collect();
+        assert_eq!(hidden, vec!["posts/scheduled.mdwn", "posts/expired.mdwn"]);
+
+        // the scheduled article is skipped by the navigation until it is published
+        let neighbours = get_prev_and_next_article(&mut conn, first.article.id.unwrap()).unwrap();
+        assert_eq!(neighbours.next.unwrap().id, third.article.id);
+
+        assert_eq!(
+            get_next_schedule_change(&mut conn, start).unwrap(),
+            Some(tomorrow)
+        );
+        assert_eq!(
+            get_next_schedule_change(&mut conn, tomorrow).unwrap(),
+            Some(next_week)
+        );
+        assert_eq!(
+            get_next_schedule_change(&mut conn, next_week).unwrap(),
+            None
+        );
+
+        let changed: Vec<String> = get_articles_with_schedule_change(&mut conn, start, next_week)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.src_file_name)
+            .collect();
+        assert_eq!(changed, vec!["posts/scheduled.mdwn", "posts/expiring.mdwn"]);
+        assert!(
+            get_articles_with_schedule_change(&mut conn, start, tomorrow - Duration::hours(1))
+                .unwrap()
+                .is_empty()
+        );
+
+        // removing the [[!publish_at]] directive publishes the article right away
+        set(
+            &mut conn,
+            &article("scheduled", "2024-01-02 10:00", None, None),
+        )
+        .unwrap();
+        assert_eq!(get_visible_articles(&mut conn).unwrap().len(), 4);
+        assert_eq!(
+            get_next_schedule_change(&mut conn, start).unwrap(),
+            Some(next_week)
+        );
+    }
+}
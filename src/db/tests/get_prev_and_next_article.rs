@@ -13,7 +13,6 @@ mod tests {
         let parsed_time1 =
             NaiveDateTime::parse_from_str("2001-01-01 01:01", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags1 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set1.mdwn".to_string(),
             dst_file_name: "test_db_set1.html".to_string(),
             title: Some("Test".to_string()),
@@ -21,11 +20,7 @@ mod tests {
             summary: Some("Test".to_string()),
             tags: Some(vec!["test1 test2 test3".to_string()]),
             series: Some("Test".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags1);
@@ -43,7 +38,6 @@ mod tests {
         let parsed_time2 =
             NaiveDateTime::parse_from_str("2003-01-01 01:01", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags2 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set2.mdwn".to_string(),
             dst_file_name: "test_db_set2.html".to_string(),
             title: Some("Test2".to_string()),
@@ -51,11 +45,7 @@ mod tests {
             summary: Some("Test2".to_string()),
             tags: Some(vec!["test2".to_string(), "test3".to_string()]),
             series: Some("Test2".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags2);
@@ -72,7 +62,6 @@ mod tests {
         let parsed_time3 =
             NaiveDateTime::parse_from_str("2002-01-01 01:01", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags3 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set3.mdwn".to_string(),
             dst_file_name: "test_db_set3.html".to_string(),
             title: Some("Test3".to_string()),
@@ -80,11 +69,7 @@ mod tests {
             summary: Some("Test3".to_string()),
             tags: Some(vec!["test3".to_string()]),
             series: Some("Test3".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags3);
@@ -93,7 +78,6 @@ mod tests {
         let parsed_time_draft =
             NaiveDateTime::parse_from_str("2002-02-02 02:02", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags_draft = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set4.mdwn".to_string(),
             dst_file_name: "test_db_set4.html".to_string(),
             title: Some("Test4".to_string()),
@@ -102,10 +86,7 @@ mod tests {
             tags: Some(vec!["test4".to_string()]),
             series: Some("Test4".to_string()),
             draft: Some(true),
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags_draft);
@@ -114,7 +95,6 @@ mod tests {
         let parsed_time_special_page =
             NaiveDateTime::parse_from_str("2002-02-02 02:02", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags_special_page = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set5.mdwn".to_string(),
             dst_file_name: "test_db_set5.html".to_string(),
             title: Some("Test5".to_string()),
@@ -122,11 +102,8 @@ mod tests {
             summary: Some("Test5".to_string()),
             tags: Some(vec!["test5".to_string()]),
             series: Some("Test5".to_string()),
-            draft: None,
             special_page: Some(true),
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags_special_page);
@@ -15,7 +15,6 @@ mod tests {
         let parsed_time1 =
             NaiveDateTime::parse_from_str("2001-01-01 01:01", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags1 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set1.mdwn".to_string(),
             dst_file_name: "test_db_set1.html".to_string(),
             title: Some("Test".to_string()),
@@ -23,11 +22,7 @@ mod tests {
             summary: Some("Test".to_string()),
             tags: Some(vec!["test1 test2 test3".to_string()]),
             series: Some("Test".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags1);
@@ -45,7 +40,6 @@ mod tests {
         let parsed_time2 =
             NaiveDateTime::parse_from_str("2003-01-01 01:01", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags2 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set2.mdwn".to_string(),
             dst_file_name: "test_db_set2.html".to_string(),
             title: Some("Test2".to_string()),
@@ -53,11 +47,7 @@ mod tests {
             summary: Some("Test2".to_string()),
             tags: Some(vec!["test2".to_string(), "test3".to_string()]),
             series: Some("Test2".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags2);
@@ -74,7 +64,6 @@ mod tests {
         let parsed_time3 =
             NaiveDateTime::parse_from_str("2002-01-01 01:01", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags3 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set3.mdwn".to_string(),
             dst_file_name: "test_db_set3.html".to_string(),
             title: Some("Test3".to_string()),
@@ -82,11 +71,7 @@ mod tests {
             summary: Some("Test3".to_string()),
             tags: Some(vec!["test3".to_string()]),
             series: Some("Test3".to_string()),
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags3);
@@ -95,7 +80,6 @@ mod tests {
         let parsed_time_draft =
             NaiveDateTime::parse_from_str("2002-02-02 02:02", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags_draft = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set4.mdwn".to_string(),
             dst_file_name: "test_db_set4.html".to_string(),
             title: Some("Test4".to_string()),
@@ -104,10 +88,7 @@ mod tests {
             tags: Some(vec!["test4".to_string()]),
             series: Some("Test4".to_string()),
             draft: Some(true),
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags_draft);
@@ -116,7 +97,6 @@ mod tests {
         let parsed_time_special_page1 =
             NaiveDateTime::parse_from_str("2002-02-02 02:02", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags_special_page1 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set5.mdwn".to_string(),
             dst_file_name: "test_db_set5.html".to_string(),
             title: Some("Test5".to_string()),
@@ -126,9 +106,7 @@ mod tests {
             series: Some("Test5".to_string()),
             draft: Some(true),
             special_page: Some(true),
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags_special_page1);
@@ -137,7 +115,6 @@ mod tests {
         let parsed_time_special_page2 =
             NaiveDateTime::parse_from_str("2002-03-03 03:03", "%Y-%m-%d %H:%M").unwrap();
         let article_with_tags_special_page2 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set9.mdwn".to_string(),
             dst_file_name: "test_db_set9.html".to_string(),
             title: Some("Test9".to_string()),
@@ -147,9 +124,7 @@ mod tests {
             series: Some("Test9".to_string()),
             draft: Some(true),
             special_page: Some(true),
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let res = set(&mut conn, &article_with_tags_special_page2);
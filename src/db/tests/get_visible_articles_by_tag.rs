@@ -11,73 +11,39 @@ mod tests {
         let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
 
         let article_with_tags1 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set1.mdwn".to_string(),
             dst_file_name: "test_db_set1.html".to_string(),
-            title: None,
-            modification_date: None,
-            summary: None,
             tags: Some(vec!["test2".to_string(), "test3".to_string()]),
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let _ = set(&mut conn, &article_with_tags1).unwrap();
 
         let article_with_tags2 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set2.mdwn".to_string(),
             dst_file_name: "test_db_set2.html".to_string(),
-            title: None,
-            modification_date: None,
-            summary: None,
             tags: Some(vec!["test2".to_string()]),
-            series: None,
-            draft: None,
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let _ = set(&mut conn, &article_with_tags2).unwrap();
 
         let article_with_tags3 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set3.mdwn".to_string(),
             dst_file_name: "test_db_set3.html".to_string(),
-            title: None,
-            modification_date: None,
-            summary: None,
             tags: Some(vec!["test2".to_string(), "test55".to_string()]),
-            series: None,
             draft: Some(true),
-            special_page: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let _ = set(&mut conn, &article_with_tags3).unwrap();
 
         let article_with_tags4 = ArticleWithTags {
-            id: None,
             src_file_name: "foo/bartest_db_set4.mdwn".to_string(),
             dst_file_name: "test_db_set4.html".to_string(),
-            title: None,
-            modification_date: None,
-            summary: None,
             tags: Some(vec!["test2".to_string(), "test55".to_string()]),
-            series: None,
-            draft: None,
             special_page: Some(true),
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
+            ..Default::default()
         };
 
         let _ = set(&mut conn, &article_with_tags4).unwrap();
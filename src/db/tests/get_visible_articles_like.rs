@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use crate::db::article::{get_visible_articles_like, set};
+    use crate::db::tests::establish_connection_and_initialize_schema;
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::articles::ArticleWithTags;
+
+    #[test]
+    fn test_db_get_visible_articles_like() {
+        let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
+
+        for (src_file_name, title, draft) in [
+            (
+                "posts/libnix/libnix_mingw_status.mdwn",
+                "libnix on Windows",
+                None,
+            ),
+            ("posts/nix.mdwn", "Nix", None),
+            ("posts/draft.mdwn", "Draft", Some(true)),
+        ] {
+            let article = ArticleWithTags {
+                src_file_name: src_file_name.to_string(),
+                dst_file_name: src_file_name.replace(".mdwn", ".html"),
+                title: Some(title.to_string()),
+                draft,
+                ..Default::default()
+            };
+            set(&mut conn, &article).unwrap();
+        }
+
+        let mut src_file_names = |patterns: &[&str]| -> Vec<String> {
+            let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+            get_visible_articles_like(&mut conn, &patterns)
+                .unwrap()
+                .into_iter()
+                .map(|article| article.src_file_name)
+                .collect()
+        };
+        assert_eq!(
+            src_file_names(&["%libnix_mingw_status.mdwn"]),
+            vec!["posts/libnix/libnix_mingw_status.mdwn"]
+        );
+        assert_eq!(
+            src_file_names(&["LIBNIX ON WINDOWS", "posts/nix.mdwn"]).len(),
+            2
+        );
+        assert!(src_file_names(&["posts/draft.mdwn", "Draft"]).is_empty());
+        assert!(src_file_names(&[]).is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use crate::db::article::set;
+    use crate::db::related::{
+        get_related_articles, get_visible_related_articles, set_all_related_articles,
+        set_related_articles, RelatedArticle,
+    };
+    use crate::db::tests::establish_connection_and_initialize_schema;
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::articles::ArticleWithTags;
+
+    fn article(name: &str, draft: Option<bool>) -> ArticleWithTags {
+        ArticleWithTags {
+            src_file_name: format!("posts/{}.mdwn", name),
+            dst_file_name: format!("{}.html", name),
+            title: Some(name.to_string()),
+            draft,
+            ..Default::default()
+        }
+    }
+
+    fn related(src: &str, related: &str, score: f64) -> RelatedArticle {
+        RelatedArticle {
+            src_file_name: format!("posts/{}.mdwn", src),
+            related_src_file_name: format!("posts/{}.mdwn", related),
+            score,
+        }
+    }
+
+    #[test]
+    fn test_db_get_visible_related_articles() {
+        let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
+        for (name, draft) in [("a", None), ("b", None), ("c", None), ("d", Some(true))] {
+            set(&mut conn, &article(name, draft)).unwrap();
+        }
+
+        set_all_related_articles(
+            &mut conn,
+            &[
+                related("a", "b", 0.2),
+                related("a", "c", 0.7),
+                related("a", "d", 0.9),
+                related("b", "a", 0.2),
+            ],
+        )
+        .unwrap();
+
+        // the draft is skipped, the others are sorted by score
+        let visible: Vec<String> = get_visible_related_articles(&mut conn, "posts/a.mdwn")
+            .unwrap()
+            .into_iter()
+            .map(|a| a.src_file_name)
+            .collect();
+        assert_eq!(visible, vec!["posts/c.mdwn", "posts/b.mdwn"]);
+        assert_eq!(
+            get_related_articles(&mut conn, "posts/a.mdwn")
+                .unwrap()
+                .len(),
+            3
+        );
+
+        set_related_articles(&mut conn, "posts/a.mdwn", &[related("a", "b", 0.5)]).unwrap();
+        assert_eq!(
+            get_related_articles(&mut conn, "posts/a.mdwn").unwrap(),
+            vec![related("a", "b", 0.5)]
+        );
+        set_related_articles(&mut conn, "posts/b.mdwn", &[]).unwrap();
+        assert!(get_related_articles(&mut conn, "posts/b.mdwn")
+            .unwrap()
+            .is_empty());
+
+        set_all_related_articles(&mut conn, &[]).unwrap();
+        assert!(get_visible_related_articles(&mut conn, "posts/a.mdwn")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -3,15 +3,23 @@ mod del_by_src_file_name;
 mod get_all_articles;
 mod get_all_series_from_visible_articles;
 mod get_all_tags;
+mod get_dependents;
 mod get_drafts;
+mod get_links_with_source;
+mod get_links_with_source_to;
 mod get_most_recent_article;
+mod get_next_schedule_change;
 mod get_prev_and_next_article;
 mod get_prev_and_next_article_for_series;
 mod get_special_pages;
 mod get_visible_articles;
 mod get_visible_articles_by_series;
 mod get_visible_articles_by_tag;
+mod get_visible_articles_like;
+mod get_visible_related_articles;
 mod set;
+mod set_dependencies;
+mod set_links;
 
 use crate::db::initialize_schema;
 use diesel::prelude::*;
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use crate::db::dependencies::{
+        del_dependencies_by_src_file_name, get_dependencies_src_file_names, get_dependents,
+        set_dependencies,
+    };
+    use crate::db::tests::establish_connection_and_initialize_schema;
+    use diesel::sqlite::SqliteConnection;
+
+    #[test]
+    fn test_db_set_dependencies() {
+        let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
+
+        set_dependencies(
+            &mut conn,
+            "posts/a.mdwn".to_string(),
+            &[
+                "media/cats".to_string(),
+                "posts/a.rs".to_string(),
+                "posts/a.rs".to_string(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            get_dependents(&mut conn, "posts/a.rs").unwrap(),
+            vec!["posts/a.mdwn".to_string()]
+        );
+
+        set_dependencies(
+            &mut conn,
+            "posts/a.mdwn".to_string(),
+            &["posts/b.rs".to_string()],
+        )
+        .unwrap();
+        assert!(get_dependents(&mut conn, "posts/a.rs").unwrap().is_empty());
+        assert_eq!(
+            get_dependents(&mut conn, "posts/b.rs").unwrap(),
+            vec!["posts/a.mdwn".to_string()]
+        );
+        assert_eq!(
+            get_dependencies_src_file_names(&mut conn).unwrap(),
+            vec!["posts/a.mdwn".to_string()]
+        );
+
+        del_dependencies_by_src_file_name(&mut conn, "posts/a.mdwn".to_string()).unwrap();
+        assert!(get_dependencies_src_file_names(&mut conn)
+            .unwrap()
+            .is_empty());
+    }
+}
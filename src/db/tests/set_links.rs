@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use crate::db::links::{get_links, get_links_src_file_names, set_links, ArticleLink};
+    use crate::db::tests::establish_connection_and_initialize_schema;
+    use diesel::sqlite::SqliteConnection;
+
+    #[test]
+    fn test_db_set_links() {
+        let mut conn: SqliteConnection = establish_connection_and_initialize_schema();
+
+        let link1 = ArticleLink {
+            src_file_name: "foo/a.mdwn".to_string(),
+            kind: "directive".to_string(),
+            target: "b".to_string(),
+        };
+        let link2 = ArticleLink {
+            src_file_name: "foo/a.mdwn".to_string(),
+            kind: "html".to_string(),
+            target: "c.html".to_string(),
+        };
+
+        set_links(
+            &mut conn,
+            "foo/a.mdwn".to_string(),
+            &[link1.clone(), link2.clone(), link2.clone()],
+        )
+        .unwrap();
+        let links = get_links(&mut conn, "foo/a.mdwn".to_string()).unwrap();
+        assert_eq!(links.len(), 2);
+        assert!(links.contains(&link1));
+        assert!(links.contains(&link2));
+
+        set_links(
+            &mut conn,
+            "foo/a.mdwn".to_string(),
+            std::slice::from_ref(&link2),
+        )
+        .unwrap();
+        let links = get_links(&mut conn, "foo/a.mdwn".to_string()).unwrap();
+        assert_eq!(links, vec![link2]);
+
+        assert_eq!(
+            get_links_src_file_names(&mut conn).unwrap(),
+            vec!["foo/a.mdwn".to_string()]
+        );
+
+        set_links(&mut conn, "foo/a.mdwn".to_string(), &[]).unwrap();
+        let links = get_links(&mut conn, "foo/a.mdwn".to_string()).unwrap();
+        assert!(links.is_empty());
+    }
+}
@@ -1,12 +1,16 @@
 use crate::registry::PubSubRegistry;
+use crate::renderer::templates;
 use diesel::prelude::*;
 use diesel::r2d2::{self, ConnectionManager};
+use notify::event::ModifyKind;
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc, Mutex};
 use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
 pub type DbPool = r2d2::Pool<ConnectionManager<SqliteConnection>>;
 #[derive(Debug, Clone)]
 pub struct PankatFileMonitorEvent {
@@ -87,123 +91,328 @@ pub fn spawn_async_monitor(
     Ok(handle)
 }
 
+/// editors write a file in several steps, the changes within this time are handled at once
+const TEMPLATE_DEBOUNCE_DURATION: Duration = Duration::from_millis(100);
+
+/// recompiles the changed templates in assets/templates, writes all pages again and sends the
+/// changes to the browsers
+pub fn spawn_template_monitor(
+    pool: DbPool,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) -> Result<JoinHandle<()>, Box<dyn std::error::Error + Send + Sync>> {
+    let templates_dir = templates::templates_dir();
+    println!(
+        "Monitoring templates directory: {}",
+        templates_dir.display()
+    );
+
+    let (tx, mut rx) = mpsc::channel::<Result<Event, notify::Error>>(DEFAULT_CHANNEL_CAPACITY);
+    let mut watcher = RecommendedWatcher::new(
+        move |res| {
+            let _ = tx.blocking_send(res);
+        },
+        Config::default(),
+    )?;
+    watcher.watch(&templates_dir, RecursiveMode::NonRecursive)?;
+
+    let handle = tokio::spawn(async move {
+        // the watcher stops when it is dropped
+        let _watcher = watcher;
+        loop {
+            tokio::select! {
+                event = rx.recv() => {
+                    let Some(event) = event else {
+                        println!("Template monitor channel closed, shutting down...");
+                        break;
+                    };
+                    let mut changed: HashSet<PathBuf> = HashSet::new();
+                    collect_template_changes(event, &mut changed);
+                    tokio::time::sleep(TEMPLATE_DEBOUNCE_DURATION).await;
+                    while let Ok(event) = rx.try_recv() {
+                        collect_template_changes(event, &mut changed);
+                    }
+                    if !changed.is_empty() {
+                        templates_changed(&pool, &changed).await;
+                    }
+                }
+                _ = shutdown_rx.recv() => {
+                    println!("Shutdown signal received in template monitor.");
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(handle)
+}
+
+fn collect_template_changes(event: Result<Event, notify::Error>, changed: &mut HashSet<PathBuf>) {
+    match event {
+        Ok(event) => {
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                changed.extend(
+                    event
+                        .paths
+                        .into_iter()
+                        .filter(|path| templates::template_name(path).is_some()),
+                );
+            }
+        }
+        Err(e) => eprintln!("Watch error: {:?}", e),
+    }
+}
+
+async fn templates_changed(pool: &DbPool, changed: &HashSet<PathBuf>) {
+    let mut reloaded = false;
+    let mut reload = false;
+    for path in changed {
+        match templates::reload(path) {
+            Ok(name) => {
+                println!("Template {} changed", name);
+                reloaded = true;
+                reload |= name == templates::STANDALONE_TEMPLATE;
+            }
+            // a broken template keeps its previous version until it is fixed
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+    if !reloaded {
+        return;
+    }
+    // renders every page, blocking, so it runs outside of the async workers
+    let pool = pool.clone();
+    let rebuild = tokio::task::spawn_blocking(move || match pool.get() {
+        Ok(mut conn) => Some(crate::articles::rebuild_all_pages(&mut conn, reload)),
+        Err(e) => {
+            println!(
+                "Error: template monitor can't get a database connection: {}",
+                e
+            );
+            None
+        }
+    });
+    let commands: Vec<(String, String)> = match rebuild.await {
+        Ok(Some(commands)) => commands,
+        Ok(None) => return,
+        Err(e) => {
+            println!("Error: rebuilding the pages failed: {}", e);
+            return;
+        }
+    };
+    for (src_file_name, command) in commands {
+        if let Some(sender) = PubSubRegistry::instance()
+            .get_sender_by_name(&src_file_name)
+            .await
+        {
+            // fails if nobody is viewing the article anymore
+            let _ = sender.send(command);
+        }
+    }
+}
+
 fn handle_event(pool: &DbPool, event: &Event) {
     let cfg = crate::config::Config::get();
     let input_path: PathBuf = cfg.input.clone();
 
     for path in &event.paths {
-        if let Some(extension) = path.extension() {
-            if extension == "mdwn" {
-                if let Ok(relative_path) = path.strip_prefix(std::env::current_dir().unwrap()) {
-                    let relative_article_path: PathBuf = relative_path
-                        .strip_prefix(input_path.clone())
-                        .unwrap()
-                        .to_path_buf();
-                    // let event_type = match event.kind {
-                    //     EventKind::Create(_) => "📝 created",
-                    //     EventKind::Modify(_) => "✏️ modified",
-                    //     EventKind::Remove(_) => "🗑️ removed",
-                    //     _ => return,
-                    // };
-                    // println!(
-                    //     "  📍 Path: {} was {}",
-                    //     relative_article_path.display(),
-                    //     event_type
-                    // );
+        if let Ok(relative_path) = path.strip_prefix(std::env::current_dir().unwrap()) {
+            let relative_article_path: PathBuf =
+                match relative_path.strip_prefix(input_path.clone()) {
+                    Ok(p) => p.to_path_buf(),
+                    Err(_) => continue,
+                };
+            // let event_type = match event.kind {
+            //     EventKind::Create(_) => "📝 created",
+            //     EventKind::Modify(_) => "✏️ modified",
+            //     EventKind::Remove(_) => "🗑️ removed",
+            //     _ => return,
+            // };
+            // println!(
+            //     "  📍 Path: {} was {}",
+            //     relative_article_path.display(),
+            //     event_type
+            // );
 
+            if relative_article_path
+                .extension()
+                .is_some_and(|extension| extension == "mdwn")
+            {
+                let pankat_event: PankatFileMonitorEvent = PankatFileMonitorEvent {
+                    kind: event.kind,
+                    path: relative_article_path.to_path_buf(),
+                };
+                debounce(pool, pankat_event);
+            }
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                // a file articles depend on, e.g. an [[!include]] or an image of a [[!gallery]]
+                let dependents: Vec<String> = match pool.get() {
+                    Ok(mut conn) => crate::db::dependencies::get_dependents(
+                        &mut conn,
+                        &relative_article_path.display().to_string(),
+                    )
+                    .unwrap_or_default(),
+                    Err(_) => Vec::new(),
+                };
+                for dependent in dependents {
+                    if dependent == relative_article_path.display().to_string() {
+                        continue;
+                    }
+                    println!(
+                        "{} changed, rebuilding {}",
+                        relative_article_path.display(),
+                        dependent
+                    );
                     let pankat_event: PankatFileMonitorEvent = PankatFileMonitorEvent {
-                        kind: event.kind,
-                        path: relative_article_path.to_path_buf(),
+                        kind: EventKind::Modify(ModifyKind::Any),
+                        path: PathBuf::from(&dependent),
                     };
-                    debounce(
-                        pool,
-                        pankat_event,
-                        relative_article_path.display().to_string(),
-                    );
+                    debounce(pool, pankat_event);
                 }
             }
         }
     }
 }
 
-fn debounce(pool: &DbPool, pankat_event: PankatFileMonitorEvent, a: String) {
-    // Debounce logic
-    use std::collections::HashMap;
-    use std::sync::Mutex;
-    use std::time::Instant;
-    use tokio::time::{sleep, Duration};
-
-    lazy_static::lazy_static! {
-        static ref EVENT_CACHE: Mutex<HashMap<PankatFileMonitorEvent, Instant>> = Mutex::new(HashMap::new());
-    }
+/// the events waiting for their debounce time, one task drains them
+#[derive(Default)]
+struct PendingEvents {
+    events: HashMap<PankatFileMonitorEvent, Instant>,
+    draining: bool,
+}
 
-    const DEBOUNCE_DURATION: Duration = Duration::from_millis(50);
+lazy_static::lazy_static! {
+    static ref PENDING_EVENTS: std::sync::Mutex<PendingEvents> = Default::default();
+}
 
-    {
-        let mut cache = EVENT_CACHE.lock().unwrap();
-        if !cache.contains_key(&pankat_event) {
-            cache.insert(pankat_event.clone(), Instant::now() + DEBOUNCE_DURATION);
-        }
-    }
+const DEBOUNCE_DURATION: Duration = Duration::from_millis(50);
 
+fn debounce(pool: &DbPool, pankat_event: PankatFileMonitorEvent) {
     let pool = pool.clone();
-    // println!(
-    //     "Free connections: {}. Connections in use: {}.",
-    //     pool.state().idle_connections,
-    //     pool.state().connections - pool.state().idle_connections
-    // );
-    let lock = Arc::new(tokio::sync::Mutex::new(()));
-    tokio::spawn(async move {
-        let (sender, _) = PubSubRegistry::instance()
-            .get_sender_receiver_by_name(a)
-            .await;
-        loop {
-            let next_event = {
-                let cache = EVENT_CACHE.lock().unwrap();
-                cache
-                    .iter()
-                    .min_by_key(|&(_, &instant)| instant)
-                    .map(|(k, &v)| (k.clone(), v))
-            };
+    queue_event(&PENDING_EVENTS, pankat_event, move |event| {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        crate::articles::file_monitor_articles_change(&mut conn, event)
+    });
+}
+
+/// queues the event and starts the task draining `pending` unless it is running already
+fn queue_event<F>(
+    pending: &'static std::sync::Mutex<PendingEvents>,
+    pankat_event: PankatFileMonitorEvent,
+    handler: F,
+) where
+    F: Fn(&PankatFileMonitorEvent) -> Result<String, String> + Send + Sync + 'static,
+{
+    let mut pending_events = pending.lock().unwrap();
+    pending_events
+        .events
+        .entry(pankat_event)
+        .or_insert_with(|| Instant::now() + DEBOUNCE_DURATION);
+    if !pending_events.draining {
+        pending_events.draining = true;
+        tokio::spawn(drain_events(pending, Arc::new(handler)));
+    }
+}
 
+/// processes the events once their debounce time is over, the result is sent to the readers
+/// of the article the event is about
+async fn drain_events<F>(pending: &'static std::sync::Mutex<PendingEvents>, handler: Arc<F>)
+where
+    F: Fn(&PankatFileMonitorEvent) -> Result<String, String> + Send + Sync + 'static,
+{
+    loop {
+        let (event, instant) = {
+            let mut pending_events = pending.lock().unwrap();
+            let next_event = pending_events
+                .events
+                .iter()
+                .min_by_key(|&(_, &instant)| instant)
+                .map(|(k, &v)| (k.clone(), v));
             match next_event {
-                Some((event, instant)) => {
-                    let now = Instant::now();
-                    if instant <= now {
-                        // Remove the entry
-                        {
-                            let mut cache = EVENT_CACHE.lock().unwrap();
-                            cache.remove(&event);
-                        }
-                        let _guard = lock.lock().await;
-
-                        println!("Processing cached event for: {}", event.path.display());
-                        match crate::articles::file_monitor_articles_change(
-                            &mut pool.get().unwrap(),
-                            &event,
-                        ) {
-                            Ok(data) => {
-                                // println!("sending the good news");
-                                //println!("sending the good news: {}", data);
-                                match sender.send(data) {
-                                    Ok(_) => {}
-                                    Err(_) => {
-                                        println!("Error sending command via websocket");
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                println!("file_monitor_articles_change Error: {:?}", e);
-                            }
-                        }
-                    } else {
-                        let duration = instant.duration_since(now);
-                        sleep(duration).await;
-                    }
+                Some(next_event) => next_event,
+                None => {
+                    // under the lock, so `queue_event` starts a new task for the next event
+                    pending_events.draining = false;
+                    return;
                 }
-                None => break,
             }
+        };
+        let now = Instant::now();
+        if instant > now {
+            tokio::time::sleep(instant.duration_since(now)).await;
+            continue;
         }
-        //println!("end of loop: tokio::spawn(async move");
-    });
+        pending.lock().unwrap().events.remove(&event);
+
+        println!("Processing cached event for: {}", event.path.display());
+        let handler = handler.clone();
+        let processed_event = event.clone();
+        // renders the article, blocking, so it runs outside of the async workers
+        let data = match tokio::task::spawn_blocking(move || handler(&processed_event)).await {
+            Ok(Ok(data)) => data,
+            Ok(Err(e)) => {
+                println!("file_monitor_articles_change Error: {:?}", e);
+                continue;
+            }
+            Err(e) => {
+                println!("Error: processing {} failed: {}", event.path.display(), e);
+                continue;
+            }
+        };
+        if let Some(sender) = PubSubRegistry::instance()
+            .get_sender_by_name(&event.path.display().to_string())
+            .await
+        {
+            if sender.send(data).is_err() {
+                println!("Error sending command via websocket");
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_drain_events_sends_to_each_article() {
+    lazy_static::lazy_static! {
+        static ref TEST_PENDING_EVENTS: std::sync::Mutex<PendingEvents> = Default::default();
+    }
+    let names = ["posts/test_debounce_a.mdwn", "posts/test_debounce_b.mdwn"];
+    let mut receivers = Vec::new();
+    for name in names {
+        let (_, receiver) = PubSubRegistry::instance()
+            .get_sender_receiver_by_name(name.to_string())
+            .await;
+        receivers.push(receiver);
+    }
+
+    // two articles including the same file
+    for name in names {
+        queue_event(
+            &TEST_PENDING_EVENTS,
+            PankatFileMonitorEvent {
+                kind: EventKind::Modify(ModifyKind::Any),
+                path: PathBuf::from(name),
+            },
+            |event| Ok(format!("html of {}", event.path.display())),
+        );
+    }
+
+    for (name, receiver) in names.iter().zip(receivers.iter_mut()) {
+        let data = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data, format!("html of {}", name));
+    }
+    tokio::time::sleep(DEBOUNCE_DURATION).await;
+    for receiver in receivers.iter_mut() {
+        assert!(receiver.try_recv().is_err());
+    }
+    let pending_events = TEST_PENDING_EVENTS.lock().unwrap();
+    assert!(pending_events.events.is_empty());
+    assert!(!pending_events.draining);
 }
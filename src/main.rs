@@ -7,8 +7,13 @@ mod file_monitor;
 mod handlers;
 mod registry;
 mod renderer;
+mod scheduler;
+use crate::articles::plugins::PluginRegistry;
 use crate::config::*;
-use crate::renderer::pandoc::check_pandoc;
+use crate::renderer::backend::get_renderer;
+use crate::renderer::diagram::{check_diagram_tool, DIAGRAM_TOOLS};
+use crate::renderer::katex::check_katex;
+use crate::renderer::templates;
 use axum::{
     routing::{get, post},
     Router,
@@ -291,16 +296,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     );
     println!("Admin password: {}", "*".repeat(cfg.admin_password.len()));
     println!("Flat filename structure: {}", cfg.flat);
+    println!("Renderer: {}", cfg.renderer);
     println!("-------------------------------------------------");
 
-    check_pandoc()?;
+    get_renderer(&cfg.renderer)?.check()?;
+    for tool in DIAGRAM_TOOLS {
+        // diagrams are optional, only their directives fail without the tool
+        if let Err(e) = check_diagram_tool(tool) {
+            let w = format!("Warning: {}, [[!{}]] is not available", e, tool.name()).yellow();
+            println!("{w}");
+        }
+    }
+    if cfg.math == "katex" {
+        check_katex()?;
+    }
+
+    // compiled once, the template monitor recompiles the changed ones
+    templates::initialize()?;
+
+    let mut plugin_registry = PluginRegistry::with_builtin_plugins();
+    plugin_registry.set_lua_plugins_dir(cfg.assets.join("plugins"));
+    println!("Plugins: {}", plugin_registry.names().join(", "));
+    PluginRegistry::initialize(plugin_registry)?;
 
     // Initialize SQLite database with Diesel
     let pool = db::establish_connection_pool();
 
     articles::collect_garbage(&pool);
-    articles::scan_articles(&pool);
-    articles::build_articles(&pool);
+    let mut timings = articles::scan_articles(&pool);
+    articles::build_articles(&pool, &mut timings);
 
     if cfg.static_build_only {
         println!("Static build only, exiting...");
@@ -321,6 +345,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         file_monitor::spawn_async_monitor(pool.clone(), cfg.input.clone(), shutdown_tx.subscribe())
             .map_err(|e| Box::<dyn std::error::Error + Send + Sync>::from(e))?;
 
+    // recompiles changed templates and rewrites all pages
+    let template_monitor_handle =
+        file_monitor::spawn_template_monitor(pool.clone(), shutdown_tx.subscribe())?;
+
+    // publishes and expires articles by their [[!publish_at]] and [[!expire_at]] dates
+    let scheduler_handle = scheduler::spawn_scheduler(pool.clone(), shutdown_tx.subscribe());
+
     let binding = cfg.subdir.clone().display().to_string();
     let subdir = binding.as_str();
 
@@ -396,6 +427,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         eprintln!("Error during monitor shutdown: {}", e);
         return Err(Box::<dyn std::error::Error + Send + Sync>::from(e));
     }
+    if let Err(e) = scheduler_handle.await {
+        eprintln!("Error during scheduler shutdown: {}", e);
+    }
+    if let Err(e) = template_monitor_handle.await {
+        eprintln!("Error during template monitor shutdown: {}", e);
+    }
     println!("Graceful shutdown complete");
 
     Ok(())
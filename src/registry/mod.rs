@@ -32,4 +32,11 @@ impl PubSubRegistry {
         let receiver = sender.subscribe();
         (sender, receiver)
     }
+
+    /// the sender if someone registered for `name`, unlike `get_sender_receiver_by_name` no
+    /// channel is created
+    pub async fn get_sender_by_name(&self, name: &str) -> Option<broadcast::Sender<String>> {
+        let channels = self.channels.lock().await;
+        channels.get(name).cloned()
+    }
 }
@@ -0,0 +1,57 @@
+use crate::renderer::markdown::PulldownCmark;
+use crate::renderer::pandoc::Pandoc;
+use std::error::Error;
+use std::path::Path;
+
+/// how an article is rendered, set with `[[!options]]`, `[[!bibliography]]` and in pankat.toml
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions<'a> {
+    pub anchors: bool,
+    pub numbered: bool,
+    /// one of `MATH_MODES`
+    pub math: &'a str,
+    pub bibliography: Option<&'a Path>,
+    pub csl: Option<&'a Path>,
+}
+
+/// turns the markdown of an article, after the plugins ran, into html
+pub trait Renderer: Sync {
+    /// the value of `renderer` in pankat.toml
+    fn name(&self) -> &'static str;
+    /// fails if the renderer can't be used, e.g. pandoc is not installed
+    fn check(&self) -> Result<(), Box<dyn Error + Send + Sync>>;
+    /// what, besides the markdown and the options, changes the html, e.g. the version of the
+    /// renderer, it is part of the cache key
+    fn cache_key(&self, options: &RenderOptions) -> Result<String, Box<dyn Error>>;
+    fn render(&self, markdown: &str, options: &RenderOptions) -> Result<String, Box<dyn Error>>;
+}
+
+pub const RENDERERS: [&dyn Renderer; 2] = [&Pandoc, &PulldownCmark];
+
+/// the renderer selected with `renderer = "..."` in pankat.toml
+pub fn get_renderer(name: &str) -> Result<&'static dyn Renderer, Box<dyn Error + Send + Sync>> {
+    RENDERERS
+        .into_iter()
+        .find(|renderer| renderer.name() == name)
+        .ok_or_else(|| {
+            format!(
+                "unknown renderer '{}', expected one of: {}",
+                name,
+                RENDERERS.map(|renderer| renderer.name()).join(", ")
+            )
+            .into()
+        })
+}
+
+#[test]
+fn test_get_renderer() {
+    assert_eq!(get_renderer("pandoc").unwrap().name(), "pandoc");
+    assert_eq!(
+        get_renderer("pulldown-cmark").unwrap().name(),
+        "pulldown-cmark"
+    );
+    assert_eq!(
+        get_renderer("comrak").err().unwrap().to_string(),
+        "unknown renderer 'comrak', expected one of: pandoc, pulldown-cmark"
+    );
+}
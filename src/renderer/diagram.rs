@@ -0,0 +1,84 @@
+use crate::renderer::utils::{check_binary, pipe_through};
+use std::error::Error;
+use std::sync::OnceLock;
+
+/// the command line tools behind `[[!graphviz]]`, `[[!plantuml]]` and `[[!mermaid]]`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DiagramTool {
+    Graphviz,
+    PlantUml,
+    Mermaid,
+}
+
+pub const DIAGRAM_TOOLS: [DiagramTool; 3] = [
+    DiagramTool::Graphviz,
+    DiagramTool::PlantUml,
+    DiagramTool::Mermaid,
+];
+
+impl DiagramTool {
+    /// the directive name
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiagramTool::Graphviz => "graphviz",
+            DiagramTool::PlantUml => "plantuml",
+            DiagramTool::Mermaid => "mermaid",
+        }
+    }
+
+    pub fn binary(&self) -> &'static str {
+        match self {
+            DiagramTool::Graphviz => "dot",
+            DiagramTool::PlantUml => "plantuml",
+            DiagramTool::Mermaid => "mmdc",
+        }
+    }
+
+    fn version_args(&self) -> &'static [&'static str] {
+        match self {
+            DiagramTool::Graphviz => &["-V"],
+            DiagramTool::PlantUml => &["-version"],
+            DiagramTool::Mermaid => &["--version"],
+        }
+    }
+
+    /// reads the diagram from stdin and writes the svg to stdout
+    fn svg_args(&self) -> &'static [&'static str] {
+        match self {
+            DiagramTool::Graphviz => &["-Tsvg"],
+            DiagramTool::PlantUml => &["-tsvg", "-pipe"],
+            DiagramTool::Mermaid => &["--input", "-", "--output", "-", "--outputFormat", "svg"],
+        }
+    }
+
+    fn availability(&self) -> &'static OnceLock<Result<(), String>> {
+        static GRAPHVIZ: OnceLock<Result<(), String>> = OnceLock::new();
+        static PLANTUML: OnceLock<Result<(), String>> = OnceLock::new();
+        static MERMAID: OnceLock<Result<(), String>> = OnceLock::new();
+        match self {
+            DiagramTool::Graphviz => &GRAPHVIZ,
+            DiagramTool::PlantUml => &PLANTUML,
+            DiagramTool::Mermaid => &MERMAID,
+        }
+    }
+}
+
+/// like `check_pandoc`, the result is remembered so directives don't spawn the tool again
+pub fn check_diagram_tool(tool: DiagramTool) -> Result<(), Box<dyn Error + Send + Sync>> {
+    tool.availability()
+        .get_or_init(|| check_binary(tool.binary(), tool.version_args()).map_err(|e| e.to_string()))
+        .clone()
+        .map_err(|e| e.into())
+}
+
+/// renders the diagram source to svg with the locally installed tool
+pub fn render_svg(tool: DiagramTool, source: &str) -> Result<String, Box<dyn Error>> {
+    check_diagram_tool(tool).map_err(|e| e.to_string())?;
+    let svg = pipe_through(tool.binary(), tool.svg_args(), source)?;
+    // skip the xml declaration and doctype, the svg is written as a file of its own but also
+    // has to work when inlined
+    match svg.find("<svg") {
+        Some(start) => Ok(svg[start..].to_string()),
+        None => Err(format!("{} did not return an svg", tool.binary()).into()),
+    }
+}
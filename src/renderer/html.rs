@@ -1,12 +1,12 @@
+use crate::articles::plugins::bibliography::split_references;
 use crate::articles::timeline::tag_links_to_timeline;
 use crate::articles::ArticleWithTags;
 use crate::config;
 use crate::db::article::ArticleNeighbours;
+use crate::renderer::templates;
 use crate::renderer::utils::date_and_time;
-use handlebars::Handlebars;
 use serde_json::json;
 use std::error::Error;
-use std::fs;
 use std::path::{PathBuf, MAIN_SEPARATOR};
 
 pub fn create_html_from_standalone_template_by_article(
@@ -23,9 +23,15 @@ pub fn create_html_from_standalone_template_by_article(
         "LiveUpdates": article.live_updates,
         "SpecialPage": article.special_page,
         "Tocify": article.tocify,
+        "KaTeX": article.math.as_deref() == Some("katex"),
         "Timeline": false,
         "ArticleSrcFileName": article.src_file_name,
         "ArticleDstFileName": article.dst_file_name,
+        "Author": article.author,
+        "Lang": article.lang,
+        "Canonical": article.canonical,
+        "Description": article.description,
+        "Keywords": article.keywords,
     });
 
     create_html_from_standalone_template(data)
@@ -34,19 +40,7 @@ pub fn create_html_from_standalone_template_by_article(
 pub fn create_html_from_standalone_template(
     data: serde_json::Value,
 ) -> Result<String, Box<dyn Error>> {
-    let cfg = config::Config::get();
-
-    let mut handlebars = Handlebars::new();
-
-    let mut assets: PathBuf = PathBuf::from(cfg.assets.clone());
-    assets.push("templates/standalone-template.html");
-    let template = assets.as_path();
-    let template_content = fs::read_to_string(template)?;
-
-    handlebars.register_template_string("standalone-template", &template_content)?;
-
-    let result = handlebars.render("standalone-template", &data)?;
-    Ok(result)
+    templates::render(templates::STANDALONE_TEMPLATE, &data)
 }
 
 pub fn create_html_from_content_template(
@@ -54,6 +48,8 @@ pub fn create_html_from_content_template(
     html: String,
     article_neighbours: ArticleNeighbours,
     article_series_neighbours: ArticleNeighbours,
+    backlinks: Vec<ArticleWithTags>,
+    related: Vec<ArticleWithTags>,
 ) -> Result<String, Box<dyn Error>> {
     let cfg = config::Config::get();
 
@@ -82,15 +78,6 @@ pub fn create_html_from_content_template(
     //     article_series_neighbours
     // );
 
-    let mut handlebars = Handlebars::new();
-
-    let mut assets: PathBuf = PathBuf::from(cfg.assets.clone());
-    assets.push("templates/content-template.html");
-    let article_template = assets.as_path();
-    let template_content = fs::read_to_string(article_template)?;
-
-    handlebars.register_template_string("content_template", &template_content)?;
-
     let articles_nav = create_html_from_navigation_articles_template(
         match article_neighbours.prev.clone() {
             Some(p) => Some(p.dst_file_name),
@@ -117,11 +104,23 @@ pub fn create_html_from_content_template(
         None => "".to_string(),
     };
 
-    let date_and_time: String = format!(
-        r#"<div id="date"><p><span id="lastupdated">{}</span></p></div>"#,
-        date_and_time(&article.modification_date)
-    )
-    .to_string();
+    let published: String = date_and_time(&article.modification_date);
+    let updated: Option<String> = match article.updated {
+        Some(updated) if Some(updated) != article.modification_date => {
+            Some(date_and_time(&article.updated))
+        }
+        _ => None,
+    };
+    let date_and_time: String = match &updated {
+        Some(updated) => format!(
+            r#"<div id="date"><p><span id="lastupdated">{}</span> <span id="updated">last updated {}</span></p></div>"#,
+            published, updated
+        ),
+        None => format!(
+            r#"<div id="date"><p><span id="lastupdated">{}</span></p></div>"#,
+            published
+        ),
+    };
 
     let tags: String = format!(
         r#"<div class="tags">{}</div>"#,
@@ -129,20 +128,50 @@ pub fn create_html_from_content_template(
     )
     .to_string();
 
+    let backlinks: Vec<serde_json::Value> = backlinks
+        .into_iter()
+        .map(|backlink| {
+            json!({
+                "title": backlink.title,
+                "dst_file_name": backlink.dst_file_name,
+            })
+        })
+        .collect();
+
+    let related: Vec<serde_json::Value> = related
+        .into_iter()
+        .map(|related| {
+            json!({
+                "title": related.title,
+                "dst_file_name": related.dst_file_name,
+            })
+        })
+        .collect();
+
+    let (html, references) = split_references(&html);
+
     let data = json!({
         "SpecialPage": article.special_page,
         "ArticlesNAV": articles_nav,
         "SeriesNAV": series_nav,
         "Title": article.title,
         "DateAndTime": date_and_time,
+        "Published": published,
+        "Updated": updated,
+        "Author": article.author,
+        "WordCount": article.word_count,
+        "CodeWordCount": article.code_word_count,
+        "ReadingTime": article.reading_time,
         "Tags": tags,
         "ArticleContent": html,
+        "References": references,
         "ArticleSrcURL": relative_path,
+        "Backlinks": backlinks,
+        "Related": related,
         "Subdir": cfg.subdir,
     });
 
-    let result = handlebars.render("content_template", &data)?;
-    Ok(result)
+    templates::render("content-template", &data)
 }
 
 pub fn create_html_from_navigation_articles_template(
@@ -151,24 +180,13 @@ pub fn create_html_from_navigation_articles_template(
 ) -> Result<String, Box<dyn Error>> {
     let cfg = config::Config::get();
 
-    let mut handlebars = Handlebars::new();
-
-    let mut assets: PathBuf = PathBuf::from(cfg.assets.clone());
-    assets.push("templates/navigation-articles-template.html");
-    let article_template = assets.as_path();
-    let template_content = fs::read_to_string(article_template)?;
-
-    handlebars.register_template_string("navigation_articles_template", &template_content)?;
-
     let data = json!({
         "article_previous_link": article_previous_link,
         "article_next_link": article_next_link,
         "Subdir": cfg.subdir,
     });
 
-    let result = handlebars.render("navigation_articles_template", &data)?;
-
-    Ok(result)
+    templates::render("navigation-articles-template", &data)
 }
 
 pub fn create_html_from_navigation_series_template(
@@ -178,15 +196,6 @@ pub fn create_html_from_navigation_series_template(
 ) -> Result<String, Box<dyn Error>> {
     let cfg = config::Config::get();
 
-    let mut handlebars = Handlebars::new();
-
-    let mut assets: PathBuf = PathBuf::from(cfg.assets.clone());
-    assets.push("templates/navigation-series-template.html");
-    let article_template = assets.as_path();
-    let template_content = fs::read_to_string(article_template)?;
-
-    handlebars.register_template_string("navigation_series_template", &template_content)?;
-
     let data = json!({
         "series": series,
         "series_previous_link": series_previous_link,
@@ -194,30 +203,15 @@ pub fn create_html_from_navigation_series_template(
         "Subdir": cfg.subdir,
     });
 
-    let result = handlebars.render("navigation_series_template", &data)?;
-
-    Ok(result)
+    templates::render("navigation-series-template", &data)
 }
 
 pub fn create_index_from_most_recent_article_template(
     most_recent_article: String,
 ) -> Result<String, Box<dyn Error>> {
-    let cfg = config::Config::get();
-
-    let mut handlebars = Handlebars::new();
-
-    let mut assets: PathBuf = PathBuf::from(cfg.assets.clone());
-    assets.push("templates/most-recent-article.html");
-    let template = assets.as_path();
-    let template_content = fs::read_to_string(template)?;
-
-    handlebars.register_template_string("most-recent-article", &template_content)?;
-
     let data = json!({
         "most_recent_article": most_recent_article,
     });
 
-    let result = handlebars.render("most-recent-article", &data)?;
-
-    Ok(result)
+    templates::render("most-recent-article", &data)
 }
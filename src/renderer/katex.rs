@@ -0,0 +1,78 @@
+use crate::articles::utils::unescape_html;
+use crate::renderer::utils::{check_binary, pipe_through};
+use regex::{Captures, Regex};
+use std::error::Error;
+use std::sync::OnceLock;
+
+/// like `check_pandoc`, the result is remembered so articles don't spawn katex again
+pub fn check_katex() -> Result<(), Box<dyn Error + Send + Sync>> {
+    static AVAILABLE: OnceLock<Result<(), String>> = OnceLock::new();
+    AVAILABLE
+        .get_or_init(|| check_binary("katex", &["--version"]).map_err(|e| e.to_string()))
+        .clone()
+        .map_err(|e| e.into())
+}
+
+fn math_regex() -> Regex {
+    Regex::new(r#"(?s)<span class="math (inline|display)">(?:\\\((.*?)\\\)|\\\[(.*?)\\\])</span>"#)
+        .unwrap()
+}
+
+/// replaces the `<span class="math ...">` elements pandoc writes with `--katex` by the html
+/// katex renders, so the page needs only the katex css and no javascript
+pub fn prerender_katex(html: &str) -> Result<String, Box<dyn Error>> {
+    prerender_math(html, |tex, display| {
+        let args: &[&str] = if display { &["-d"] } else { &[] };
+        pipe_through("katex", args, tex)
+    })
+}
+
+fn prerender_math<F>(html: &str, mut render: F) -> Result<String, Box<dyn Error>>
+where
+    F: FnMut(&str, bool) -> Result<String, Box<dyn Error>>,
+{
+    let mut error: Option<Box<dyn Error>> = None;
+    let result = math_regex().replace_all(html, |captures: &Captures| {
+        let display = &captures[1] == "display";
+        let tex = captures
+            .get(2)
+            .or_else(|| captures.get(3))
+            .map_or("", |tex| tex.as_str());
+        match render(&unescape_html(tex), display) {
+            Ok(rendered) => rendered.trim_end().to_string(),
+            Err(e) => {
+                error.get_or_insert(format!("katex: can't render '{}': {}", tex, e).into());
+                captures[0].to_string()
+            }
+        }
+    });
+    match error {
+        Some(e) => Err(e),
+        None => Ok(result.into_owned()),
+    }
+}
+
+#[test]
+fn test_prerender_math() {
+    let html = concat!(
+        r#"<p>Euler <span class="math inline">\(e^{i\pi} &lt; 1\)</span></p>"#,
+        "\n",
+        r#"<p><span class="math display">\[a &amp; b\]</span></p>"#
+    );
+    let rendered = prerender_math(html, |tex, display| {
+        Ok(format!("<katex display={}>{}</katex>\n", display, tex))
+    })
+    .unwrap();
+    assert_eq!(
+        rendered,
+        concat!(
+            "<p>Euler <katex display=false>e^{i\\pi} < 1</katex></p>\n",
+            "<p><katex display=true>a & b</katex></p>"
+        )
+    );
+
+    assert!(prerender_math(html, |_, _| Err("not installed".into()))
+        .unwrap_err()
+        .to_string()
+        .starts_with("katex: can't render"));
+}
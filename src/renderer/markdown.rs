@@ -0,0 +1,428 @@
+use crate::articles::utils::escape_html;
+use crate::renderer::backend::{RenderOptions, Renderer};
+use crate::renderer::katex::prerender_katex;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::{styled_line_to_highlighted_html, IncludeBackground};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// renders without pandoc, headings, anchors and code blocks are written like the pandoc
+/// renderer with the lua filters writes them so the css and the javascript work for both
+///
+/// citations are not supported and `math=mathml` leaves the tex as it is, `math=katex`
+/// works as with pandoc.
+pub struct PulldownCmark;
+
+/// like `create-anchors.lua`
+const ANCHOR_STYLE: &str = "font-size: medium; font-style: normal; font-variant: normal; font-weight: normal; line-height: 1; padding-left: 0.375em; vertical-align: middle;";
+
+impl Renderer for PulldownCmark {
+    fn name(&self) -> &'static str {
+        "pulldown-cmark"
+    }
+
+    fn check(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        Ok(())
+    }
+
+    /// the renderer is built in, a new pankat version might render differently
+    fn cache_key(&self, _options: &RenderOptions) -> Result<String, Box<dyn Error>> {
+        Ok(format!("pankat {}", env!("CARGO_PKG_VERSION")))
+    }
+
+    fn render(&self, markdown: &str, options: &RenderOptions) -> Result<String, Box<dyn Error>> {
+        if options.bibliography.is_some() {
+            return Err("bibliography: citations need the pandoc renderer".into());
+        }
+        let html = markdown_to_html(markdown, options)?;
+        if options.math == "katex" {
+            return prerender_katex(&html);
+        }
+        Ok(html)
+    }
+}
+
+/// the pandoc extensions pulldown-cmark knows about
+fn parser_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_SUPERSCRIPT
+        | Options::ENABLE_SUBSCRIPT
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_SMART_PUNCTUATION
+        | Options::ENABLE_HEADING_ATTRIBUTES
+        | Options::ENABLE_DEFINITION_LIST
+        | Options::ENABLE_MATH
+}
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+    THEME.get_or_init(|| ThemeSet::load_defaults().themes["InspiredGitHub"].clone())
+}
+
+/// pandoc's auto identifier: lowercase, punctuation removed, spaces become hyphens and
+/// everything before the first letter is dropped
+fn identifier(text: &str) -> String {
+    let id: String = text
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ' ' | '\n'))
+        .map(|c| if c.is_whitespace() { '-' } else { c })
+        .flat_map(|c| c.to_lowercase())
+        .skip_while(|c| !c.is_alphabetic())
+        .collect();
+    if id.is_empty() {
+        "section".to_string()
+    } else {
+        id
+    }
+}
+
+/// numbers the headings like pandoc's `make_sections`, the top level is the highest level
+/// used in the article
+struct Headings {
+    ids: HashMap<String, usize>,
+    numbers: [usize; 6],
+    top_level: usize,
+    anchors: bool,
+    numbered: bool,
+}
+
+impl Headings {
+    /// a `-1`, `-2`, ... suffix makes the identifier unique
+    fn unique_id(&mut self, id: String) -> String {
+        let count = self.ids.entry(id.clone()).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            id
+        } else {
+            format!("{}-{}", id, *count - 1)
+        }
+    }
+
+    fn number(&mut self, level: usize) -> String {
+        self.numbers[level - 1] += 1;
+        self.numbers[level..].iter_mut().for_each(|n| *n = 0);
+        self.numbers[self.top_level - 1..level]
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<String>>()
+            .join(".")
+    }
+
+    /// the heading shifted by one level, like `shifted-numbered-headings.lua` does
+    fn html(&mut self, heading: Tag, content: Vec<Event>) -> String {
+        let Tag::Heading {
+            level,
+            id,
+            classes,
+            attrs,
+        } = heading
+        else {
+            return String::new();
+        };
+        let level = level as usize;
+        let text: String = content
+            .iter()
+            .filter_map(|event| match event {
+                Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+                _ => None,
+            })
+            .collect();
+        let id = self.unique_id(match id {
+            Some(id) => id.to_string(),
+            None => identifier(&text),
+        });
+        let unnumbered = classes
+            .iter()
+            .any(|class| class.as_ref() == "unnumbered" || class.as_ref() == "-");
+
+        let mut attributes = format!(r#" id="{}""#, escape_html(&id));
+        let classes: Vec<&str> = classes
+            .iter()
+            .map(|class| class.as_ref())
+            .filter(|class| *class != "-")
+            .collect();
+        if !classes.is_empty() {
+            attributes.push_str(&format!(r#" class="{}""#, escape_html(&classes.join(" "))));
+        }
+        for (key, value) in &attrs {
+            attributes.push_str(&format!(
+                r#" {}="{}""#,
+                escape_html(key),
+                escape_html(value.as_deref().unwrap_or_default())
+            ));
+        }
+        let mut number = String::new();
+        if self.numbered && !unnumbered {
+            let n = self.number(level);
+            attributes.push_str(&format!(r#" data-number="{}""#, n));
+            number = format!(r#"<span class="header-section-number">{}</span> "#, n);
+        }
+        let mut inner = String::new();
+        pulldown_cmark::html::push_html(&mut inner, content.into_iter());
+        let anchor = if self.anchors {
+            format!(
+                r##"<a class="glyphicon glyphicon-link" aria-label="Anchor"  href="#{}" style="{}"></a>"##,
+                escape_html(&id),
+                ANCHOR_STYLE
+            )
+        } else {
+            String::new()
+        };
+        let h = (level + 1).min(6);
+        format!("<h{h}{attributes}>{number}{inner}{anchor}</h{h}>\n")
+    }
+}
+
+/// the language and line numbers of ```` ```rust ```` or ```` ``` {.rust .numberLines startFrom="3"} ````
+#[derive(Debug, Default, PartialEq)]
+struct CodeAttributes {
+    lang: Option<String>,
+    number_lines: bool,
+    start_from: usize,
+}
+
+fn code_attributes(info: &str) -> CodeAttributes {
+    let info = info.trim();
+    let mut attributes = CodeAttributes {
+        start_from: 1,
+        ..Default::default()
+    };
+    match info.strip_prefix('{').and_then(|i| i.strip_suffix('}')) {
+        Some(pandoc_attributes) => {
+            for attribute in pandoc_attributes.split_whitespace() {
+                if let Some(class) = attribute.strip_prefix('.') {
+                    match class {
+                        "numberLines" | "number-lines" => attributes.number_lines = true,
+                        lang if attributes.lang.is_none() => {
+                            attributes.lang = Some(lang.to_string())
+                        }
+                        _ => {}
+                    }
+                } else if let Some((key, value)) = attribute.split_once('=') {
+                    if key == "startFrom" {
+                        attributes.start_from = value.trim_matches('"').parse().unwrap_or(1);
+                    }
+                }
+            }
+        }
+        None => attributes.lang = info.split_whitespace().next().map(|l| l.to_string()),
+    }
+    attributes
+}
+
+/// the highlighted lines, the colors are inlined so no stylesheet is needed
+fn highlight(lang: Option<&str>, code: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let syntax = lang.and_then(|lang| syntax_set().find_syntax_by_token(lang));
+    match syntax {
+        Some(syntax) => {
+            let mut highlighter = HighlightLines::new(syntax, theme());
+            LinesWithEndings::from(code)
+                .map(|line| {
+                    let regions = highlighter.highlight_line(line, syntax_set())?;
+                    let html = styled_line_to_highlighted_html(&regions, IncludeBackground::No)?;
+                    Ok(html.replace('\n', ""))
+                })
+                .collect()
+        }
+        None => Ok(code.lines().map(escape_html).collect()),
+    }
+}
+
+fn code_block_html(info: &str, code: &str) -> Result<String, Box<dyn Error>> {
+    let attributes = code_attributes(info);
+    let lines = highlight(attributes.lang.as_deref(), code)?;
+    let lines: Vec<String> = if attributes.number_lines {
+        lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, line)| {
+                format!(
+                    r#"<a class="sourceLine" data-line-number="{}">{}</a>"#,
+                    attributes.start_from + i,
+                    line
+                )
+            })
+            .collect()
+    } else {
+        lines
+    };
+    let lines = lines.join("\n");
+    let lang = attributes
+        .lang
+        .as_deref()
+        .map(escape_html)
+        .unwrap_or_default();
+    if lang.is_empty() && !attributes.number_lines {
+        return Ok(format!("<pre><code>{}</code></pre>\n", lines));
+    }
+    let number_source = if attributes.number_lines {
+        "numberSource "
+    } else {
+        ""
+    };
+    Ok(format!(
+        r#"<div class="sourceCode"><pre class="sourceCode {}{}"><code class="sourceCode {}">{}</code></pre></div>"#,
+        number_source, lang, lang, lines
+    ) + "\n")
+}
+
+/// math is written like pandoc writes it without `--mathml`
+fn math_html(tex: &str, display: bool) -> String {
+    if display {
+        format!(
+            r#"<span class="math display">\[{}\]</span>"#,
+            escape_html(tex)
+        )
+    } else {
+        format!(
+            r#"<span class="math inline">\({}\)</span>"#,
+            escape_html(tex)
+        )
+    }
+}
+
+fn markdown_to_html(markdown: &str, options: &RenderOptions) -> Result<String, Box<dyn Error>> {
+    let events: Vec<Event> = Parser::new_ext(markdown, parser_options())
+        .map(|event| match event {
+            Event::InlineMath(tex) => Event::InlineHtml(CowStr::from(math_html(&tex, false))),
+            Event::DisplayMath(tex) => Event::InlineHtml(CowStr::from(math_html(&tex, true))),
+            event => event,
+        })
+        .collect();
+    let top_level = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Start(Tag::Heading { level, .. }) => Some(*level as usize),
+            _ => None,
+        })
+        .min()
+        .unwrap_or(1);
+    let mut headings = Headings {
+        ids: HashMap::new(),
+        numbers: [0; 6],
+        top_level,
+        anchors: options.anchors,
+        numbered: options.numbered,
+    };
+
+    let mut output: Vec<Event> = Vec::new();
+    let mut events = events.into_iter();
+    while let Some(event) = events.next() {
+        match event {
+            Event::Start(heading @ Tag::Heading { .. }) => {
+                let content: Vec<Event> = events
+                    .by_ref()
+                    .take_while(|event| !matches!(event, Event::End(TagEnd::Heading(_))))
+                    .collect();
+                output.push(Event::Html(CowStr::from(headings.html(heading, content))));
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let code: String = events
+                    .by_ref()
+                    .take_while(|event| !matches!(event, Event::End(TagEnd::CodeBlock)))
+                    .filter_map(|event| match event {
+                        Event::Text(text) => Some(text.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                let info = match kind {
+                    CodeBlockKind::Fenced(info) => info.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                output.push(Event::Html(CowStr::from(code_block_html(&info, &code)?)));
+            }
+            event => output.push(event),
+        }
+    }
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, output.into_iter());
+    Ok(html)
+}
+
+#[test]
+fn test_markdown_to_html() {
+    let options = RenderOptions {
+        anchors: false,
+        numbered: true,
+        math: "none",
+        bibliography: None,
+        csl: None,
+    };
+    let html = PulldownCmark
+        .render(
+            "# Nix on *Windows*\n\n## Setup\n\n## Setup\n\n# Usage {#use .unnumbered}\n\ncost $x^2$\n",
+            &options,
+        )
+        .unwrap();
+    assert_eq!(
+        html,
+        concat!(
+            "<h2 id=\"nix-on-windows\" data-number=\"1\"><span class=\"header-section-number\">1</span> Nix on <em>Windows</em></h2>\n",
+            "<h3 id=\"setup\" data-number=\"1.1\"><span class=\"header-section-number\">1.1</span> Setup</h3>\n",
+            "<h3 id=\"setup-1\" data-number=\"1.2\"><span class=\"header-section-number\">1.2</span> Setup</h3>\n",
+            "<h2 id=\"use\" class=\"unnumbered\">Usage</h2>\n",
+            "<p>cost <span class=\"math inline\">\\(x^2\\)</span></p>\n"
+        )
+    );
+
+    let options = RenderOptions {
+        anchors: true,
+        numbered: false,
+        ..options
+    };
+    let html = PulldownCmark.render("### 2. Step", &options).unwrap();
+    assert!(html.starts_with(
+        r##"<h4 id="step">2. Step<a class="glyphicon glyphicon-link" aria-label="Anchor"  href="#step" style="##
+    ));
+
+    let html = PulldownCmark
+        .render(
+            "``` {.rust .numberLines startFrom=\"3\"}\nfn main() {}\n```\n\n```\n<b>\n```\n",
+            &options,
+        )
+        .unwrap();
+    assert!(html.starts_with(
+        r#"<div class="sourceCode"><pre class="sourceCode numberSource rust"><code class="sourceCode rust"><a class="sourceLine" data-line-number="3"><span style="#
+    ));
+    assert!(html.ends_with("<pre><code>&lt;b&gt;</code></pre>\n"));
+
+    let bibliography = std::path::PathBuf::from("refs.bib");
+    let options = RenderOptions {
+        bibliography: Some(&bibliography),
+        ..options
+    };
+    assert!(PulldownCmark.render("[@knuth84]", &options).is_err());
+}
+
+#[test]
+fn test_code_attributes() {
+    assert_eq!(
+        code_attributes("rust"),
+        CodeAttributes {
+            lang: Some("rust".to_string()),
+            number_lines: false,
+            start_from: 1,
+        }
+    );
+    assert_eq!(
+        code_attributes(r#"{.numberLines .c startFrom="10"}"#),
+        CodeAttributes {
+            lang: Some("c".to_string()),
+            number_lines: true,
+            start_from: 10,
+        }
+    );
+    assert_eq!(code_attributes("").lang, None);
+}
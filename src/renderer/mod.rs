@@ -1,4 +1,9 @@
+pub mod backend;
+pub mod diagram;
 pub mod html;
+pub mod katex;
+pub mod markdown;
 pub mod pandoc;
+pub mod templates;
 mod tests;
 pub mod utils;
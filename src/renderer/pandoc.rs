@@ -1,9 +1,46 @@
 use crate::config;
+use crate::db::cache::compute_hash;
+use crate::renderer::backend::{RenderOptions, Renderer};
+use crate::renderer::katex::prerender_katex;
 use std::error::Error;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
-pub fn check_pandoc() -> Result<(), Box<dyn Error + Send + Sync>> {
+/// values of `math` in pankat.toml and of `[[!options math=...]]`
+pub const MATH_MODES: [&str; 3] = ["none", "mathml", "katex"];
+
+/// the default renderer, the headings are changed by the lua filters in assets/pandoc-lua
+pub struct Pandoc;
+
+impl Renderer for Pandoc {
+    fn name(&self) -> &'static str {
+        "pandoc"
+    }
+
+    fn check(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        check_pandoc()
+    }
+
+    fn cache_key(&self, options: &RenderOptions) -> Result<String, Box<dyn Error>> {
+        pandoc_cache_key(options)
+    }
+
+    fn render(&self, markdown: &str, options: &RenderOptions) -> Result<String, Box<dyn Error>> {
+        pandoc_mdwn_2_html(markdown, options)
+    }
+}
+
+/// the first line of `pandoc --version`, the result is remembered as it is part of the cache key
+pub fn pandoc_version() -> Result<String, Box<dyn Error + Send + Sync>> {
+    static VERSION: OnceLock<Result<String, String>> = OnceLock::new();
+    VERSION
+        .get_or_init(|| read_pandoc_version().map_err(|e| e.to_string()))
+        .clone()
+        .map_err(|e| e.into())
+}
+
+fn read_pandoc_version() -> Result<String, Box<dyn Error + Send + Sync>> {
     let pandoc_process = std::process::Command::new("pandoc")
         .arg("--version")
         .stdin(std::process::Stdio::piped())
@@ -17,19 +54,9 @@ pub fn check_pandoc() -> Result<(), Box<dyn Error + Send + Sync>> {
                 if output.status.success() {
                     let stdout = String::from_utf8_lossy(&output.stdout);
                     //println!("{}", stdout);
-                    let re = regex::Regex::new(r"pandoc (\d+)\.").unwrap();
-                    if let Some(captures) = re.captures(&stdout) {
-                        if let Some(major_version) = captures.get(1) {
-                            if let Ok(major) = major_version.as_str().parse::<u32>() {
-                                if major >= 3 {
-                                    return Ok(());
-                                }
-                            }
-                        }
-                    }
-                    return Err("Pandoc version 3 or newer not found!".into());
+                    Ok(stdout.lines().next().unwrap_or_default().trim().to_string())
                 } else {
-                    return Err("Failed to execute pandoc process".into());
+                    Err("Failed to execute pandoc process".into())
                 }
             }
             Err(e) => Err(format!("Failed to execute pandoc process: {}", e).into()),
@@ -38,41 +65,96 @@ pub fn check_pandoc() -> Result<(), Box<dyn Error + Send + Sync>> {
     }
 }
 
-pub fn pandoc_mdwn_2_html(
-    article_markdown: String,
-    tocify: bool,
-) -> Result<String, Box<dyn Error>> {
-    // println!("-------------------------");
-    // println!("{}", article_markdown.clone());
-    // println!("-------------------------");
+pub fn check_pandoc() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let version = pandoc_version()?;
+    let re = regex::Regex::new(r"pandoc (\d+)\.").unwrap();
+    if let Some(captures) = re.captures(&version) {
+        if let Some(major_version) = captures.get(1) {
+            if let Ok(major) = major_version.as_str().parse::<u32>() {
+                if major >= 3 {
+                    return Ok(());
+                }
+            }
+        }
+    }
+    Err("Pandoc version 3 or newer not found!".into())
+}
+
+/// the lua filters in assets/pandoc-lua, `create-anchors.lua` only with anchors
+fn lua_filters(options: &RenderOptions) -> Vec<PathBuf> {
     let cfg = config::Config::get();
-    let mut assets: PathBuf = PathBuf::from(cfg.assets.clone());
-    assets.push("pandoc-lua/shifted-numbered-headings.lua");
-    let shifted_numbered_headings = assets.display().to_string();
+    let mut filters: Vec<PathBuf> =
+        vec![cfg.assets.join("pandoc-lua/shifted-numbered-headings.lua")];
+    if options.anchors {
+        filters.push(cfg.assets.join("pandoc-lua/create-anchors.lua"));
+    }
+    filters
+}
 
-    let mut assets2: PathBuf = PathBuf::from(cfg.assets.clone());
-    assets2.push("pandoc-lua/create-anchors.lua");
-    let create_anchors = assets2.display().to_string();
+/// the command line of pandoc for the options
+fn pandoc_args(options: &RenderOptions) -> Vec<String> {
+    let mut args: Vec<String> = vec![];
 
-    // Print the command line for debugging
-    //println!("Executing pandoc with command: pandoc --lua-filter {:?} --lua-filter {:?} -f markdown -t html5 --highlight-style kate", luafile, luafile2);
+    for filter in lua_filters(options) {
+        args.push("--lua-filter".to_string());
+        args.push(filter.display().to_string());
+    }
+
+    // read by the lua filters, see [[!options numbered=false]]
+    if !options.numbered {
+        args.push("-M".to_string());
+        args.push("pankat-numbered=false".to_string());
+    }
+
+    match options.math {
+        "mathml" => args.push("--mathml".to_string()),
+        // pandoc only wraps the tex, katex renders it after pandoc is done
+        "katex" => args.push("--katex".to_string()),
+        _ => {}
+    }
 
-    let mut args = vec![];
+    // see [[!bibliography refs.bib style=ieee.csl]]
+    if let Some(bibliography) = options.bibliography {
+        args.push("--citeproc".to_string());
+        args.push("--bibliography".to_string());
+        args.push(bibliography.display().to_string());
+        args.push("-M".to_string());
+        args.push("link-citations=true".to_string());
+        if let Some(csl) = options.csl {
+            args.push("--csl".to_string());
+            args.push(csl.display().to_string());
+        }
+    }
 
-    if tocify {
-        args.push("--lua-filter");
-        args.push(&shifted_numbered_headings);
+    for arg in ["-f", "markdown", "-t", "html5", "--highlight-style", "kate"] {
+        args.push(arg.to_string());
     }
+    args
+}
 
-    args.push("--lua-filter");
-    args.push(&create_anchors);
+/// the pandoc version, the arguments and the content of the lua filters
+fn pandoc_cache_key(options: &RenderOptions) -> Result<String, Box<dyn Error>> {
+    let version = pandoc_version().map_err(|e| e.to_string())?;
+    let mut key = format!("{}\nargs={}", version, pandoc_args(options).join(" "));
+    for filter in lua_filters(options) {
+        let content = std::fs::read_to_string(&filter)
+            .map_err(|e| format!("Can't read lua filter {}: {}", filter.display(), e))?;
+        key.push_str(&format!("\n{}={}", filter.display(), compute_hash(content)));
+    }
+    Ok(key)
+}
 
-    args.push("-f");
-    args.push("markdown");
-    args.push("-t");
-    args.push("html5");
-    args.push("--highlight-style");
-    args.push("kate");
+pub fn pandoc_mdwn_2_html(
+    article_markdown: &str,
+    options: &RenderOptions,
+) -> Result<String, Box<dyn Error>> {
+    // println!("-------------------------");
+    // println!("{}", article_markdown.clone());
+    // println!("-------------------------");
+    let args = pandoc_args(options);
+
+    // Print the command line for debugging
+    //println!("Executing pandoc with command: pandoc {}", args.join(" "));
 
     let mut pandoc_process = std::process::Command::new("pandoc")
         .args(args)
@@ -93,6 +175,9 @@ pub fn pandoc_mdwn_2_html(
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        if options.math == "katex" {
+            return prerender_katex(&stdout);
+        }
         Ok(stdout)
     } else {
         Err("Pandoc process failed".into())
@@ -0,0 +1,124 @@
+use crate::config;
+use handlebars::Handlebars;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+/// the page around the article, when it changes the browsers reload instead of updating the
+/// article
+pub const STANDALONE_TEMPLATE: &str = "standalone-template";
+
+/// the templates compiled once, every template is named after its file, e.g.
+/// `content-template` for `content-template.html`, and can be used as a partial by the others
+static REGISTRY: OnceLock<RwLock<Handlebars<'static>>> = OnceLock::new();
+
+/// assets/templates
+pub fn templates_dir() -> PathBuf {
+    config::Config::get().assets.join("templates")
+}
+
+/// the name of the template for a file in assets/templates, `None` for other files
+pub fn template_name(path: &Path) -> Option<String> {
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "html")
+    {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+    } else {
+        None
+    }
+}
+
+fn load_templates(templates_dir: &Path) -> Result<Handlebars<'static>, Box<dyn Error>> {
+    let mut handlebars = Handlebars::new();
+    let entries = std::fs::read_dir(templates_dir)
+        .map_err(|e| format!("Can't read templates {}: {}", templates_dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if let Some(name) = template_name(&path) {
+            let template_content = std::fs::read_to_string(&path)?;
+            handlebars
+                .register_template_string(&name, &template_content)
+                .map_err(|e| format!("Template {}: {}", path.display(), e))?;
+        }
+    }
+    Ok(handlebars)
+}
+
+/// compiles the templates, called on startup so broken templates are reported right away
+pub fn initialize() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let handlebars = load_templates(&templates_dir()).map_err(|e| e.to_string())?;
+    REGISTRY
+        .set(RwLock::new(handlebars))
+        .map_err(|_| "Templates are already initialized".into())
+}
+
+fn registry() -> &'static RwLock<Handlebars<'static>> {
+    REGISTRY.get_or_init(|| match load_templates(&templates_dir()) {
+        Ok(handlebars) => RwLock::new(handlebars),
+        Err(e) => {
+            println!("Error: {}", e);
+            RwLock::new(Handlebars::new())
+        }
+    })
+}
+
+pub fn render(name: &str, data: &serde_json::Value) -> Result<String, Box<dyn Error>> {
+    let handlebars = registry()
+        .read()
+        .map_err(|_| "Template registry is poisoned")?;
+    Ok(handlebars.render(name, data)?)
+}
+
+/// compiles a changed template again, a removed one is unregistered, on errors the previous
+/// version stays in use, returns the name of the template
+pub fn reload(path: &Path) -> Result<String, Box<dyn Error>> {
+    let name = template_name(path).ok_or_else(|| format!("{} is no template", path.display()))?;
+    let mut handlebars = registry()
+        .write()
+        .map_err(|_| "Template registry is poisoned")?;
+    if path.exists() {
+        let template_content = std::fs::read_to_string(path)?;
+        handlebars
+            .register_template_string(&name, &template_content)
+            .map_err(|e| format!("Template {}: {}", path.display(), e))?;
+    } else {
+        handlebars.unregister_template(&name);
+    }
+    Ok(name)
+}
+
+#[test]
+fn test_load_templates() {
+    let templates_dir =
+        std::env::temp_dir().join(format!("pankat-test-templates-{}", std::process::id()));
+    std::fs::create_dir_all(&templates_dir).unwrap();
+    std::fs::write(templates_dir.join("page.html"), "<p>{{> title}}</p>").unwrap();
+    std::fs::write(templates_dir.join("title.html"), "{{Title}}").unwrap();
+    std::fs::write(templates_dir.join("notes.txt"), "{{#if}}").unwrap();
+
+    let handlebars = load_templates(&templates_dir).unwrap();
+    assert_eq!(
+        handlebars
+            .render("page", &serde_json::json!({"Title": "pankat"}))
+            .unwrap(),
+        "<p>pankat</p>"
+    );
+    assert_eq!(handlebars.get_templates().len(), 2);
+
+    std::fs::write(templates_dir.join("broken.html"), "{{#if}}").unwrap();
+    assert!(load_templates(&templates_dir)
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("broken.html"));
+
+    assert_eq!(
+        template_name(Path::new("a/content-template.html")).as_deref(),
+        Some("content-template")
+    );
+    assert_eq!(template_name(Path::new("a/content-template.html~")), None);
+
+    std::fs::remove_dir_all(&templates_dir).unwrap();
+}
@@ -93,19 +93,11 @@ mod tests {
         config::Config::initialize(config).expect("Failed to initialize config");
 
         let article = ArticleWithTags {
-            id: None,
             src_file_name: "documents/blog.lastlog.de/posts/test_src.md".to_string(),
             dst_file_name: "test_dst.html".to_string(),
             title: Some("Test NewArticle".to_string()),
-            modification_date: None,
-            summary: None,
-            series: None,
             special_page: Some(true),
-            draft: None,
-            anchorjs: None,
-            tocify: None,
-            live_updates: None,
-            tags: None,
+            ..Default::default()
         };
         let html_content = "<p>This is a test body.</p>".to_string();
 
@@ -118,6 +110,129 @@ mod tests {
         assert!(rendered_html.contains("Test NewArticle"));
     }
 
+    #[test]
+    fn test_front_matter_description() {
+        use crate::articles::front_matter::split_front_matter;
+
+        let config = create_hacky_config();
+        config::Config::initialize(config).expect("Failed to initialize config");
+
+        let (front_matter, _) =
+            split_front_matter("---\nsummary: the summary\ndescription: the description\n---\n")
+                .unwrap();
+        let mut article = ArticleWithTags {
+            src_file_name: "posts/test_src.mdwn".to_string(),
+            dst_file_name: "test_src.html".to_string(),
+            ..Default::default()
+        };
+        front_matter.unwrap().apply(&mut article).unwrap();
+        assert_eq!(article.summary.as_deref(), Some("the summary"));
+
+        let html = create_html_from_standalone_template_by_article(article, String::new()).unwrap();
+        assert!(html.contains(r#"<meta name="description" content="the description" />"#));
+    }
+
+    #[test]
+    fn test_create_html_from_content_template_backlinks() {
+        use crate::db::article::ArticleNeighbours;
+        use crate::renderer::html::create_html_from_content_template;
+
+        let config = create_hacky_config();
+        config::Config::initialize(config).expect("Failed to initialize config");
+
+        let article = ArticleWithTags {
+            src_file_name: "posts/test_src.mdwn".to_string(),
+            dst_file_name: "test_src.html".to_string(),
+            title: Some("Test NewArticle".to_string()),
+            ..Default::default()
+        };
+        let mut backlink = article.clone();
+        backlink.src_file_name = "posts/backlink.mdwn".to_string();
+        backlink.dst_file_name = "backlink.html".to_string();
+        backlink.title = Some("Backlink Article".to_string());
+
+        let result = create_html_from_content_template(
+            article.clone(),
+            "<p>body</p>".to_string(),
+            ArticleNeighbours::new(),
+            ArticleNeighbours::new(),
+            vec![backlink.clone()],
+            vec![],
+        )
+        .unwrap();
+        assert!(result.contains("referenced by"));
+        assert!(result.contains(r#"/backlink.html">Backlink Article</a>"#));
+        assert!(!result.contains("You might also like"));
+
+        let result = create_html_from_content_template(
+            article.clone(),
+            "<p>body</p>".to_string(),
+            ArticleNeighbours::new(),
+            ArticleNeighbours::new(),
+            vec![],
+            vec![backlink],
+        )
+        .unwrap();
+        assert!(result.contains("You might also like"));
+        assert!(result.contains(r#"/backlink.html">Backlink Article</a>"#));
+
+        let result = create_html_from_content_template(
+            article,
+            "<p>body</p>".to_string(),
+            ArticleNeighbours::new(),
+            ArticleNeighbours::new(),
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        assert!(!result.contains("referenced by"));
+    }
+
+    #[test]
+    fn test_create_html_from_content_template_updated() {
+        use crate::db::article::ArticleNeighbours;
+        use crate::renderer::html::create_html_from_content_template;
+        use chrono::NaiveDateTime;
+
+        let config = create_hacky_config();
+        config::Config::initialize(config).expect("Failed to initialize config");
+
+        let mut article = ArticleWithTags {
+            src_file_name: "posts/test_src.mdwn".to_string(),
+            dst_file_name: "test_src.html".to_string(),
+            title: Some("Test NewArticle".to_string()),
+            modification_date: NaiveDateTime::parse_from_str("2024-04-12 20:53", "%Y-%m-%d %H:%M")
+                .ok(),
+            author: Some("Joachim".to_string()),
+            ..Default::default()
+        };
+
+        let result = create_html_from_content_template(
+            article.clone(),
+            "<p>body</p>".to_string(),
+            ArticleNeighbours::new(),
+            ArticleNeighbours::new(),
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        assert!(result.contains(r#"<span id="lastupdated">12 apr 2024</span>"#));
+        assert!(!result.contains("last updated"));
+        assert!(result.contains(r#"<div id="author">by Joachim</div>"#));
+
+        article.updated = NaiveDateTime::parse_from_str("2025-01-10 09:00", "%Y-%m-%d %H:%M").ok();
+        let result = create_html_from_content_template(
+            article,
+            "<p>body</p>".to_string(),
+            ArticleNeighbours::new(),
+            ArticleNeighbours::new(),
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        assert!(result.contains(r#"<span id="updated">last updated 10 jan 2025</span>"#));
+    }
+
     #[test]
     fn test_date_and_time() {
         use crate::renderer::utils::date_and_time;
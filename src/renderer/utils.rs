@@ -1,3 +1,6 @@
+use std::error::Error;
+use std::io::Write;
+
 pub fn date_and_time(modification_date: &Option<chrono::NaiveDateTime>) -> String {
     match modification_date {
         Some(modification_date) => modification_date
@@ -7,3 +10,78 @@ pub fn date_and_time(modification_date: &Option<chrono::NaiveDateTime>) -> Strin
         None => String::new(),
     }
 }
+
+/// runs `binary args` to check it is installed, e.g. `dot -V`
+pub fn check_binary(binary: &str, args: &[&str]) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let process = std::process::Command::new(binary)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn();
+
+    match process {
+        Ok(process) => match process.wait_with_output() {
+            Ok(output) => {
+                if output.status.success() {
+                    Ok(())
+                } else {
+                    Err(format!("Failed to execute {} process", binary).into())
+                }
+            }
+            Err(e) => Err(format!("Failed to execute {} process: {}", binary, e).into()),
+        },
+        Err(e) => Err(format!("Can't find '{}' binary: {}", binary, e).into()),
+    }
+}
+
+/// pipes `input` through `binary args` and returns its stdout
+pub fn pipe_through(binary: &str, args: &[&str], input: &str) -> Result<String, Box<dyn Error>> {
+    let mut process = std::process::Command::new(binary)
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Can't find '{}' binary: {}", binary, e))?;
+    let mut stdin = process.stdin.take().ok_or("Failed to open stdin")?;
+
+    // stdin is written by another thread while stdout is read, otherwise both block once the
+    // output fills the pipe buffer
+    let (written, output) = std::thread::scope(|scope| {
+        let writer = scope.spawn(move || stdin.write_all(input.as_bytes()));
+        let output = process.wait_with_output();
+        (writer.join(), output)
+    });
+    let output = output?;
+
+    if output.status.success() {
+        written
+            .map_err(|_| format!("Writing to {} panicked", binary))?
+            .map_err(|e| format!("Can't write to {}: {}", binary, e))?;
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "{} process failed: {}",
+            binary,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into())
+    }
+}
+
+#[test]
+fn test_pipe_through() {
+    assert_eq!(
+        pipe_through("cat", &[], "<svg></svg>").unwrap(),
+        "<svg></svg>"
+    );
+    let large = "<svg></svg>\n".repeat(100_000);
+    assert_eq!(pipe_through("cat", &[], &large).unwrap(), large);
+    let error = pipe_through("pankat-missing-binary", &[], "")
+        .unwrap_err()
+        .to_string();
+    assert!(error.starts_with("Can't find 'pankat-missing-binary' binary"));
+    assert!(check_binary("pankat-missing-binary", &[]).is_err());
+    assert!(check_binary("cat", &["--version"]).is_ok());
+}
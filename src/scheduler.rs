@@ -0,0 +1,72 @@
+use crate::db::article::{get_next_schedule_change, now};
+use crate::db::DbPool;
+use tokio::sync::{broadcast, Notify};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+/// wake up at least this often, the system clock might have been changed
+const MAX_SLEEP: Duration = Duration::from_secs(60 * 60);
+
+lazy_static::lazy_static! {
+    static ref RESCHEDULE: Notify = Notify::new();
+}
+
+/// called after an article changed, its `[[!publish_at]]` or `[[!expire_at]]` might be new
+pub fn reschedule() {
+    RESCHEDULE.notify_one();
+}
+
+/// keeps a timer for the next `[[!publish_at]]` or `[[!expire_at]]` date and rebuilds the
+/// affected articles, their neighbours, the timeline and index.html when it passes
+pub fn spawn_scheduler(pool: DbPool, mut shutdown_rx: broadcast::Receiver<()>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last_update = now();
+        loop {
+            let next = match pool.get() {
+                Ok(mut conn) => match get_next_schedule_change(&mut conn, last_update) {
+                    Ok(next) => next,
+                    Err(e) => {
+                        println!("Error: loading the next scheduled article failed: {}", e);
+                        None
+                    }
+                },
+                Err(e) => {
+                    println!("Error: scheduler can't get a database connection: {}", e);
+                    None
+                }
+            };
+            let duration: Duration = match next {
+                Some(next) => {
+                    println!("⏰ next scheduled article change at {}", next);
+                    (next - now())
+                        .to_std()
+                        .unwrap_or(Duration::ZERO)
+                        .min(MAX_SLEEP)
+                }
+                None => MAX_SLEEP,
+            };
+
+            tokio::select! {
+                _ = tokio::time::sleep(duration) => {}
+                _ = RESCHEDULE.notified() => {}
+                _ = shutdown_rx.recv() => {
+                    println!("Shutdown signal received in scheduler.");
+                    break;
+                }
+            }
+
+            let current = now();
+            // rebuilds the affected pages, blocking, so it runs outside of the async workers
+            let update_pool = pool.clone();
+            let update = tokio::task::spawn_blocking(move || {
+                if let Ok(mut conn) = update_pool.get() {
+                    crate::articles::update_scheduled_articles(&mut conn, last_update, current);
+                }
+            });
+            if let Err(e) = update.await {
+                println!("Error: updating the scheduled articles failed: {}", e);
+            }
+            last_update = current;
+        }
+    })
+}